@@ -0,0 +1,140 @@
+#!/usr/bin/env perl
+# Regenerates src/unicode/tables.rs from the Unicode database bundled with perl.
+#
+#     perl scripts/unicode_tables.pl > src/unicode/tables.rs
+
+use strict;
+use warnings;
+use Unicode::UCD qw(prop_invlist prop_invmap prop_values prop_value_aliases);
+
+my @CATEGORIES = qw(Cc Cf Co Ll Lm Lo Lt Lu Mc Me Mn Nd Nl No Pc Pd Pe Pf Pi Po Ps Sc Sk Sm So Zl Zp Zs);
+my @BINARY_PROPERTIES = (
+    [ 'Alphabetic',   'Alpha' ],
+    [ 'ID_Continue',  'IDC' ],
+    [ 'ID_Start',     'IDS' ],
+    [ 'Lowercase',    'Lower' ],
+    [ 'Uppercase',    'Upper' ],
+    [ 'White_Space',  'WSpace', 'space' ],
+    [ 'XID_Continue', 'XIDC' ],
+    [ 'XID_Start',    'XIDS' ],
+);
+
+sub ranges {
+    my @list = prop_invlist($_[0]);
+    my @ranges;
+    for (my $i = 0; $i < @list; $i += 2) {
+        my $start = $list[$i];
+        my $end = $i + 1 < @list ? $list[$i + 1] - 1 : 0x10FFFF;
+        for my $piece ([ $start, $end < 0xD800 ? $end : 0xD7FF ], [ $start > 0xDFFF ? $start : 0xE000, $end ]) {
+            push @ranges, $piece if $piece->[0] <= $piece->[1];
+        }
+    }
+    return @ranges;
+}
+
+sub aliases {
+    my ($property, $value) = @_;
+    my %seen;
+    return grep { !$seen{$_}++ } prop_value_aliases($property, $value);
+}
+
+sub constant_name {
+    my $name = uc $_[0];
+    $name =~ s/[^A-Z0-9]+/_/g;
+    return $name;
+}
+
+sub emit_table {
+    my ($name, @ranges) = @_;
+    my @pairs = map { sprintf "('\\u{%X}', '\\u{%X}')", @$_ } @ranges;
+    print "const $name: &[(char, char)] = &[\n";
+    my $line = '   ';
+    for my $pair (@pairs) {
+        if (length($line) + length($pair) + 2 > 120) {
+            print "$line\n";
+            $line = '   ';
+        }
+        $line .= " $pair,";
+    }
+    print "$line\n" if $line ne '   ';
+    print "];\n\n";
+}
+
+sub emit_case_folding {
+    my ($list, $map) = prop_invmap('Simple_Case_Folding');
+    my %orbits;
+    for my $i (0 .. $#$list - 1) {
+        next if $map->[$i] == 0;
+        for my $code ($list->[$i] .. $list->[$i + 1] - 1) {
+            my $folded = $map->[$i] + $code - $list->[$i];
+            $orbits{$folded}{$folded} = 1;
+            $orbits{$folded}{$code} = 1;
+        }
+    }
+
+    my %others;
+    for my $orbit (values %orbits) {
+        my @members = sort { $a <=> $b } keys %$orbit;
+        for my $member (@members) {
+            $others{$member} = [ grep { $_ != $member } @members ];
+        }
+    }
+
+    print "pub const CASE_FOLDING: &[(char, &[char])] = &[\n";
+    my $line = '   ';
+    for my $code (sort { $a <=> $b } keys %others) {
+        my $entry = sprintf "('\\u{%X}', &[%s]),", $code, join ', ', map { sprintf "'\\u{%X}'", $_ } @{ $others{$code} };
+        if (length($line) + length($entry) + 1 > 120) {
+            print "$line\n";
+            $line = '   ';
+        }
+        $line .= " $entry";
+    }
+    print "$line\n" if $line ne '   ';
+    print "];\n";
+}
+
+sub emit_index {
+    my ($name, @entries) = @_;
+    print "pub const $name: &[Property] = &[\n";
+    for my $entry (@entries) {
+        my ($table, @aliases) = @$entry;
+        my $names = join ', ', map { "\"$_\"" } @aliases;
+        print "    (&[$names], $table),\n";
+    }
+    print "];\n\n";
+}
+
+print "// \@generated by scripts/unicode_tables.pl from Unicode ", Unicode::UCD::UnicodeVersion(), ", do not edit by hand\n\n";
+print "use super::Property;\n\n";
+
+my @categories;
+for my $category (@CATEGORIES) {
+    my @aliases = aliases('gc', $category);
+    my $table = 'GC_' . constant_name($aliases[1] // $aliases[0]);
+    emit_table($table, ranges("gc=$category"));
+    push @categories, [ $table, @aliases ];
+}
+
+my @scripts;
+for my $script (sort(prop_values('sc'))) {
+    my @ranges = ranges("sc=$script");
+    next unless @ranges;
+    my @aliases = aliases('sc', $script);
+    my $table = 'SC_' . constant_name($aliases[1] // $aliases[0]);
+    emit_table($table, @ranges);
+    push @scripts, [ $table, @aliases ];
+}
+
+my @binary_properties;
+for my $property (@BINARY_PROPERTIES) {
+    my ($long, @aliases) = @$property;
+    my $table = constant_name($long);
+    emit_table($table, ranges($long));
+    push @binary_properties, [ $table, $long, @aliases ];
+}
+
+emit_index('GENERAL_CATEGORIES', @categories);
+emit_index('SCRIPTS', @scripts);
+emit_index('BINARY_PROPERTIES', @binary_properties);
+emit_case_folding();
@@ -1,10 +1,16 @@
 use crate::span::Span;
 
+pub const DEFAULT_MODE: &str = "initial";
+pub const MAX_REPETITION: usize = 1000;
+
 #[derive(Debug)]
 pub struct Rule<'src> {
     pub name: &'src str,
     pub name_span: Span,
     pub kind: RuleKind,
+    pub is_case_insensitive: bool,
+    pub mode: &'src str,
+    pub action: Option<RuleAction<'src>>,
     pub pattern: ASTNode<'src>,
 }
 
@@ -15,7 +21,14 @@ pub enum RuleKind {
     Skip,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy)]
+pub enum RuleAction<'src> {
+    Push { mode: &'src str, span: Span },
+    Pop { span: Span },
+    Enter { mode: &'src str, span: Span },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ASTNode<'src> {
     Literal {
         char: char,
@@ -34,31 +47,49 @@ pub enum ASTNode<'src> {
     },
     Repeat0 {
         inner: Box<ASTNode<'src>>,
+        span: Span,
     },
     Repeat1 {
         inner: Box<ASTNode<'src>>,
     },
     Optional {
         inner: Box<ASTNode<'src>>,
+        span: Span,
     },
     Group {
         inner: Box<ASTNode<'src>>,
         span: Span,
     },
+    CaseInsensitive {
+        inner: Box<ASTNode<'src>>,
+        span: Span,
+    },
     Range {
         inner: Box<ASTNode<'src>>,
         min: usize,
         max: Option<usize>,
+        span: Span,
+    },
+    TrailingContext {
+        head: Box<ASTNode<'src>>,
+        tail: Box<ASTNode<'src>>,
+        span: Span,
     },
     CharClass {
         negated: bool,
         parts: Vec<CharClassPart>,
     },
     AnyChar,
+    LineStart {
+        span: Span,
+    },
+    LineEnd {
+        span: Span,
+    },
     Error,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CharClassPart {
     Literal { char: char },
     Range { min: char, max: char },
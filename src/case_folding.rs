@@ -0,0 +1,126 @@
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast::{ASTNode, Rule},
+    regex::{parts_to_ranges, ranges_to_parts},
+    unicode::case_fold_ranges,
+};
+
+pub fn fold_case(rules: &mut [Rule]) {
+    let mut patterns: FxHashMap<&str, ASTNode> = FxHashMap::default();
+    for rule in rules.iter() {
+        patterns.entry(rule.name).or_insert_with(|| rule.pattern.clone());
+    }
+
+    for rule in rules {
+        let mut folder = CaseFolder {
+            patterns: &patterns,
+            references: Vec::new(),
+        };
+        folder.fold_node(&mut rule.pattern, rule.is_case_insensitive);
+    }
+}
+
+pub fn is_case_insensitive(rules: &[Rule]) -> bool {
+    rules
+        .iter()
+        .any(|rule| rule.is_case_insensitive || has_case_insensitive_group(&rule.pattern))
+}
+
+fn has_case_insensitive_group(node: &ASTNode) -> bool {
+    match node {
+        ASTNode::CaseInsensitive { .. } => true,
+        ASTNode::Alternation { left, right }
+        | ASTNode::Concat { left, right }
+        | ASTNode::TrailingContext {
+            head: left,
+            tail: right,
+            ..
+        } => has_case_insensitive_group(left) || has_case_insensitive_group(right),
+        ASTNode::Repeat0 { inner, .. }
+        | ASTNode::Repeat1 { inner }
+        | ASTNode::Optional { inner, .. }
+        | ASTNode::Group { inner, .. }
+        | ASTNode::Range { inner, .. } => has_case_insensitive_group(inner),
+        ASTNode::Literal { .. }
+        | ASTNode::Reference { .. }
+        | ASTNode::CharClass { .. }
+        | ASTNode::AnyChar
+        | ASTNode::LineStart { .. }
+        | ASTNode::LineEnd { .. }
+        | ASTNode::Error => false,
+    }
+}
+
+struct CaseFolder<'a, 'src> {
+    patterns: &'a FxHashMap<&'src str, ASTNode<'src>>,
+    references: Vec<&'src str>,
+}
+
+impl<'src> CaseFolder<'_, 'src> {
+    fn fold_node(&mut self, node: &mut ASTNode<'src>, is_case_insensitive: bool) {
+        match node {
+            ASTNode::Literal { char } if is_case_insensitive => {
+                let ranges = case_fold_ranges(&[(*char, *char)]);
+                if ranges.len() > 1 {
+                    *node = ASTNode::CharClass {
+                        negated: false,
+                        parts: ranges_to_parts(&ranges),
+                    };
+                }
+            }
+            ASTNode::CharClass { parts, .. } if is_case_insensitive => {
+                *parts = ranges_to_parts(&case_fold_ranges(&parts_to_ranges(parts)));
+            }
+            ASTNode::CaseInsensitive { inner, .. } => self.fold_node(inner, true),
+            ASTNode::Alternation { left, right }
+            | ASTNode::Concat { left, right }
+            | ASTNode::TrailingContext {
+                head: left,
+                tail: right,
+                ..
+            } => {
+                self.fold_node(left, is_case_insensitive);
+                self.fold_node(right, is_case_insensitive);
+            }
+            ASTNode::Repeat0 { inner, .. }
+            | ASTNode::Repeat1 { inner }
+            | ASTNode::Optional { inner, .. }
+            | ASTNode::Group { inner, .. }
+            | ASTNode::Range { inner, .. } => self.fold_node(inner, is_case_insensitive),
+            ASTNode::Reference { name, .. } if is_case_insensitive => {
+                let Some(pattern) = self.patterns.get(name) else {
+                    return;
+                };
+                if self.references.contains(name) {
+                    return;
+                }
+
+                let name = *name;
+                *node = pattern.clone();
+                self.references.push(name);
+                self.fold_node(node, true);
+                self.references.pop();
+            }
+            ASTNode::Literal { .. }
+            | ASTNode::Reference { .. }
+            | ASTNode::CharClass { .. }
+            | ASTNode::AnyChar
+            | ASTNode::LineStart { .. }
+            | ASTNode::LineEnd { .. }
+            | ASTNode::Error => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile;
+
+    #[test]
+    fn case_insensitivity_carries_into_referenced_helpers() {
+        let spec = compile("helper letter = [a-z]\nnocase word = {letter}+\ninline = (?i:{letter})1\n").unwrap();
+        assert_eq!(spec.dfa.longest_match(0, b"ABC", 0), Some((1, 3)));
+        assert_eq!(spec.dfa.longest_match(0, b"Q1", 0), Some((2, 2)));
+    }
+}
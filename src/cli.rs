@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use lexx::codegen::Strategy;
+
+pub const USAGE: &str = "\
+Usage: lexx <COMMAND> [OPTIONS]
+
+Commands:
+  check <SPEC>              Check a specification for errors and warnings
+  build <SPEC> [-o <FILE>]  Generate a Rust lexer from a specification
+  lex <SPEC> <INPUT>        Tokenize INPUT with the specification and print each token
+
+Options:
+  -o, --output <FILE>       Write the generated lexer to FILE instead of stdout
+  -s, --strategy <NAME>     Code generation strategy, either 'table' (default) or 'direct'
+  -D, --deny-warnings       Exit with a nonzero status if there are warnings
+  -h, --help                Print this help message";
+
+#[derive(Debug)]
+pub enum Command {
+    Check {
+        spec: PathBuf,
+        deny_warnings: bool,
+    },
+    Build {
+        spec: PathBuf,
+        output: Option<PathBuf>,
+        strategy: Strategy,
+        deny_warnings: bool,
+    },
+    Lex {
+        spec: PathBuf,
+        input: PathBuf,
+        deny_warnings: bool,
+    },
+    Help,
+}
+
+pub fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let Some(command) = args.next() else {
+        return Ok(Command::Help);
+    };
+
+    let mut positional: Vec<String> = Vec::new();
+    let mut output: Option<PathBuf> = None;
+    let mut strategy = Strategy::Table;
+    let mut deny_warnings = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-D" | "--deny-warnings" => deny_warnings = true,
+            "-o" | "--output" => {
+                let path = args.next().ok_or_else(|| format!("'{arg}' requires a file path"))?;
+                output = Some(PathBuf::from(path));
+            }
+            "-s" | "--strategy" => {
+                let name = args.next().ok_or_else(|| format!("'{arg}' requires a strategy name"))?;
+                strategy = match name.as_str() {
+                    "table" => Strategy::Table,
+                    "direct" => Strategy::Direct,
+                    _ => return Err(format!("unknown strategy '{name}', expected 'table' or 'direct'")),
+                };
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => return Err(format!("unknown option '{arg}'")),
+            _ => positional.push(arg),
+        }
+    }
+
+    match command.as_str() {
+        "-h" | "--help" | "help" => Ok(Command::Help),
+        "check" => {
+            let [spec] = expect_arguments(&command, positional, ["<SPEC>"])?;
+            Ok(Command::Check { spec, deny_warnings })
+        }
+        "build" => {
+            let [spec] = expect_arguments(&command, positional, ["<SPEC>"])?;
+            Ok(Command::Build {
+                spec,
+                output,
+                strategy,
+                deny_warnings,
+            })
+        }
+        "lex" => {
+            let [spec, input] = expect_arguments(&command, positional, ["<SPEC>", "<INPUT>"])?;
+            Ok(Command::Lex {
+                spec,
+                input,
+                deny_warnings,
+            })
+        }
+        _ => Err(format!("unknown command '{command}'")),
+    }
+}
+
+fn expect_arguments<const N: usize>(
+    command: &str,
+    positional: Vec<String>,
+    names: [&str; N],
+) -> Result<[PathBuf; N], String> {
+    if positional.len() != N {
+        return Err(format!("'{command}' expects {}", names.join(" ")));
+    }
+    let paths: Vec<PathBuf> = positional.into_iter().map(PathBuf::from).collect();
+    Ok(paths.try_into().unwrap_or_else(|_| unreachable!()))
+}
@@ -0,0 +1,134 @@
+use std::fmt::Write;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    dfa::{DEAD_STATE, DfaState, StateId},
+    nfa::RuleId,
+};
+
+use super::RustGenerator;
+
+impl RustGenerator<'_> {
+    pub(super) fn emit_direct_matcher(&mut self) {
+        let dfa = self.dfa;
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "fn longest_match(bytes: &[u8], start: usize, start_state: usize) -> Option<(Action, usize)> {{"
+        );
+        let _ = writeln!(output, "    let mut state = start_state;");
+        let _ = writeln!(output, "    let mut last_match = None;");
+        let _ = writeln!(output, "    for (index, &byte) in bytes[start..].iter().enumerate() {{");
+        let _ = writeln!(output, "        state = match state {{");
+        for (state_id, state) in dfa.states.iter().enumerate() {
+            let arms = byte_arms(state_id, &state.transitions, &dfa.byte_classes);
+            if arms.is_empty() {
+                continue;
+            }
+            let _ = writeln!(output, "            {state_id} => match byte {{");
+            for (to, patterns) in group_by_target(arms) {
+                let _ = writeln!(output, "                {} => {to},", patterns.join(" | "));
+            }
+            let _ = writeln!(output, "                _ => break,");
+            let _ = writeln!(output, "            }},");
+        }
+        let _ = writeln!(output, "            _ => break,");
+        let _ = writeln!(output, "        }};");
+
+        let line_end_arms =
+            self.accept_arms(|state| state.line_end_accept.filter(|_| state.line_end_accept != state.accept));
+        let arms = self.accept_arms(|state| state.accept);
+        if !line_end_arms.is_empty() || !arms.is_empty() {
+            let _ = writeln!(output, "        let end = start + index + 1;");
+            if !line_end_arms.is_empty() {
+                let _ = writeln!(output, "        let is_line_end = end == bytes.len() || bytes[end] == b'\\n';");
+            }
+            let _ = writeln!(output, "        match state {{");
+            for (action, states) in line_end_arms {
+                let _ = writeln!(
+                    output,
+                    "            {} if is_line_end => last_match = Some(({action}, end)),",
+                    states.join(" | ")
+                );
+            }
+            for (action, states) in arms {
+                let _ = writeln!(
+                    output,
+                    "            {} => last_match = Some(({action}, end)),",
+                    states.join(" | ")
+                );
+            }
+            let _ = writeln!(output, "            _ => {{}}");
+            let _ = writeln!(output, "        }}");
+        }
+        let _ = writeln!(output, "    }}");
+        let _ = writeln!(output, "    last_match");
+        let _ = writeln!(output, "}}");
+        self.output.push_str(&output);
+    }
+
+    fn accept_arms(&self, accept: impl Fn(&DfaState) -> Option<RuleId>) -> Vec<(String, Vec<String>)> {
+        let mut states_by_action: Vec<(String, Vec<String>)> = Vec::new();
+        let mut action_index: FxHashMap<String, usize> = FxHashMap::default();
+        for (state_id, state) in self.dfa.states.iter().enumerate() {
+            let Some(rule_id) = accept(state) else {
+                continue;
+            };
+            let action = self.action(Some(rule_id));
+            let index = *action_index.entry(action.clone()).or_insert_with(|| {
+                states_by_action.push((action, Vec::new()));
+                states_by_action.len() - 1
+            });
+            states_by_action[index].1.push(state_id.to_string());
+        }
+        states_by_action
+    }
+}
+
+fn byte_arms(state_id: StateId, transitions: &[StateId], byte_classes: &[u8; 256]) -> Vec<(u8, u8, StateId)> {
+    let mut arms: Vec<(u8, u8, StateId)> = Vec::new();
+    if state_id == DEAD_STATE {
+        return arms;
+    }
+
+    for byte in 0..=255u8 {
+        let to = transitions[byte_classes[byte as usize] as usize];
+        if to == DEAD_STATE {
+            continue;
+        }
+        match arms.last_mut() {
+            Some((_, end, last_to)) if *last_to == to && *end as usize + 1 == byte as usize => *end = byte,
+            _ => arms.push((byte, byte, to)),
+        }
+    }
+    arms
+}
+
+fn group_by_target(arms: Vec<(u8, u8, StateId)>) -> Vec<(StateId, Vec<String>)> {
+    let mut groups: Vec<(StateId, Vec<String>)> = Vec::new();
+    for (start, end, to) in arms {
+        let pattern = byte_pattern(start, end);
+        match groups.iter_mut().find(|(target, _)| *target == to) {
+            Some((_, patterns)) => patterns.push(pattern),
+            None => groups.push((to, vec![pattern])),
+        }
+    }
+    groups
+}
+
+fn byte_pattern(start: u8, end: u8) -> String {
+    if start == end {
+        byte_literal(start)
+    } else {
+        format!("{}..={}", byte_literal(start), byte_literal(end))
+    }
+}
+
+fn byte_literal(byte: u8) -> String {
+    if byte.is_ascii_alphanumeric() {
+        format!("b'{}'", byte as char)
+    } else {
+        format!("0x{byte:02X}")
+    }
+}
@@ -0,0 +1,354 @@
+use std::fmt::Write;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast::RuleKind,
+    compile::{CompiledRule, CompiledSpec, ModeChange},
+    dfa::Dfa,
+    error::{Error, ErrorKind},
+    nfa::RuleId,
+};
+
+mod direct;
+mod table;
+mod trailing;
+
+const RESERVED_VARIANTS: [&str; 2] = ["Error", "Self"];
+
+const HEADER: &str = "\
+// @generated by lexx, do not edit by hand
+
+";
+
+const TOKEN_TYPES: &str = "\
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+";
+
+const LEXER: &str = "\
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    input: &'a str,
+    offset: usize,
+    modes: Vec<usize>,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            offset: 0,
+            modes: vec![0],
+        }
+    }
+
+    pub fn slice(&self, token: &Token) -> &'a str {
+        &self.input[token.span.start..token.span.end]
+    }
+
+    pub fn mode(&self) -> usize {
+        self.modes.last().copied().unwrap_or_default()
+    }
+
+    fn change_mode(&mut self, mode_change: ModeChange) {
+        match mode_change {
+            ModeChange::None => {}
+            ModeChange::Push(mode) => self.modes.push(mode),
+            ModeChange::Pop => {
+                if self.modes.len() > 1 {
+                    self.modes.pop();
+                }
+            }
+            ModeChange::Enter(mode) => {
+                if let Some(top) = self.modes.last_mut() {
+                    *top = mode;
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        loop {
+            let start = self.offset;
+            if start >= self.input.len() {
+                return None;
+            }
+
+            let bytes = self.input.as_bytes();
+            let start_state = if start == 0 || bytes[start - 1] == b'\\n' {
+                LINE_START_STATES[self.mode()]
+            } else {
+                START_STATES[self.mode()]
+            };
+            match longest_match(bytes, start, start_state) {
+                Some((Action::Token(kind, mode_change, head_end), end)) => {
+                    let end = head_end.map_or(end, |head_end| head_end(bytes, start, end));
+                    self.offset = end;
+                    self.change_mode(mode_change);
+                    let span = Span { start, end };
+                    return Some(Token { kind, span });
+                }
+                Some((Action::Skip(mode_change, head_end), end)) => {
+                    self.offset = head_end.map_or(end, |head_end| head_end(bytes, start, end));
+                    self.change_mode(mode_change);
+                }
+                _ => {
+                    let len = self.input[start..].chars().next().map_or(1, char::len_utf8);
+                    self.offset = start + len;
+                    let span = Span { start, end: self.offset };
+                    return Some(Token { kind: TokenKind::Error, span });
+                }
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    None,
+    Skip(ModeChange, Option<HeadEnd>),
+    Token(TokenKind, ModeChange, Option<HeadEnd>),
+}
+
+type HeadEnd = fn(&[u8], usize, usize) -> usize;
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+enum ModeChange {
+    None,
+    Push(usize),
+    Pop,
+    Enter(usize),
+}
+
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Table,
+    Direct,
+}
+
+#[derive(Debug)]
+pub struct RustGenerator<'a> {
+    rules: &'a [CompiledRule],
+    dfa: &'a Dfa,
+    strategy: Strategy,
+    variants: Vec<Option<String>>,
+    output: String,
+}
+
+impl<'a> RustGenerator<'a> {
+    pub fn new(spec: &'a CompiledSpec, strategy: Strategy) -> Self {
+        Self {
+            rules: &spec.rules,
+            dfa: &spec.dfa,
+            strategy,
+            variants: Vec::new(),
+            output: String::new(),
+        }
+    }
+
+    pub fn generate(mut self) -> Result<String, Vec<Error>> {
+        self.variants = self.token_variants()?;
+
+        self.output.push_str(HEADER);
+        self.emit_token_kind();
+        self.output.push_str(TOKEN_TYPES);
+        self.output.push_str(LEXER);
+        self.emit_start_states();
+        match self.strategy {
+            Strategy::Table => self.emit_table_matcher(),
+            Strategy::Direct => self.emit_direct_matcher(),
+        }
+        self.emit_trailing_contexts();
+        Ok(self.output)
+    }
+
+    fn token_variants(&self) -> Result<Vec<Option<String>>, Vec<Error>> {
+        let mut errors = Vec::new();
+        let mut variants = Vec::new();
+        let mut rules_by_variant: FxHashMap<String, &CompiledRule> = FxHashMap::default();
+
+        for rule in self.rules {
+            if !matches!(rule.kind, RuleKind::Token) {
+                variants.push(None);
+                continue;
+            }
+
+            let variant = to_variant_name(&rule.name);
+            if variant.is_empty() || RESERVED_VARIANTS.contains(&variant.as_str()) {
+                errors.push(Error {
+                    kind: ErrorKind::ReservedTokenName { variant },
+                    span: rule.name_span,
+                });
+                variants.push(None);
+                continue;
+            }
+
+            if let Some(first) = rules_by_variant.get(&variant) {
+                errors.push(Error {
+                    kind: ErrorKind::TokenNameCollision {
+                        variant: variant.clone(),
+                        first_span: first.name_span,
+                    },
+                    span: rule.name_span,
+                });
+            } else {
+                rules_by_variant.insert(variant.clone(), rule);
+            }
+            variants.push(Some(variant));
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(variants)
+    }
+
+    fn emit_token_kind(&mut self) {
+        let mut output = String::new();
+        let _ = writeln!(output, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]");
+        let _ = writeln!(output, "pub enum TokenKind {{");
+        for variant in self.variants.iter().flatten() {
+            let _ = writeln!(output, "    {variant},");
+        }
+        let _ = writeln!(output, "    Error,");
+        let _ = writeln!(output, "}}");
+        let _ = writeln!(output);
+        self.output.push_str(&output);
+    }
+
+    fn emit_start_states(&mut self) {
+        for (name, states) in [("START_STATES", &self.dfa.starts), ("LINE_START_STATES", &self.dfa.line_starts)] {
+            let states: Vec<String> = states.iter().map(usize::to_string).collect();
+            let _ = writeln!(
+                self.output,
+                "static {name}: [usize; {}] = [{}];",
+                states.len(),
+                states.join(", ")
+            );
+        }
+        let _ = writeln!(self.output);
+    }
+
+    fn action(&self, accept: Option<RuleId>) -> String {
+        let Some(rule_id) = accept else {
+            return String::from("Action::None");
+        };
+        let mode_change = match self.rules[rule_id].mode_change {
+            None => String::from("ModeChange::None"),
+            Some(ModeChange::Push(mode)) => format!("ModeChange::Push({mode})"),
+            Some(ModeChange::Pop) => String::from("ModeChange::Pop"),
+            Some(ModeChange::Enter(mode)) => format!("ModeChange::Enter({mode})"),
+        };
+        let head_end = match self.rules[rule_id].trailing_context {
+            Some(_) => format!("Some(trailing_context_{rule_id})"),
+            None => String::from("None"),
+        };
+        match &self.variants[rule_id] {
+            Some(variant) => format!("Action::Token(TokenKind::{variant}, {mode_change}, {head_end})"),
+            None => format!("Action::Skip({mode_change}, {head_end})"),
+        }
+    }
+}
+
+fn to_variant_name(rule_name: &str) -> String {
+    let mut variant = String::new();
+    for segment in rule_name.split('_').filter(|segment| !segment.is_empty()) {
+        let mut chars = segment.chars();
+        if let Some(first) = chars.next() {
+            variant.extend(first.to_uppercase());
+            variant.push_str(chars.as_str());
+        }
+    }
+    variant
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fmt::Write, fs, process::{self, Command}};
+
+    use crate::{compile, interpreter::Interpreter};
+
+    use super::{RustGenerator, Strategy, to_variant_name};
+
+    const SPEC: &str = r#"directive = ^#[a-z]+
+hash = #
+last_word = [a-z]+$
+range_start = \d+/\.\.
+int = \d+
+dot_dot = \.\.
+word = [a-z]+
+quote -> push(string) = \"
+skip space = [ \n]+
+
+mode string
+string_end -> pop = \"
+text = [^"\n]+
+"#;
+
+    const INPUT: &str = "#define x 1..20 \"a #b\" #y\nfoo bar\n  #end 3 @\n\"open\nz";
+
+    fn run_generated(strategy: Strategy) -> String {
+        let spec = compile(SPEC).unwrap();
+        let mut source = RustGenerator::new(&spec, strategy).generate().unwrap();
+        let _ = writeln!(source, "const INPUT: &str = {INPUT:?};");
+        source.push_str("fn main() {\n    for token in Lexer::new(INPUT) {\n        println!(\"{:?} {}..{}\", token.kind, token.span.start, token.span.end);\n    }\n}\n");
+
+        let name = format!("{strategy:?}").to_lowercase();
+        let dir = env::temp_dir().join(format!("lexx-codegen-{}-{name}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source_path = dir.join("lexer.rs");
+        let binary_path = dir.join("lexer");
+        fs::write(&source_path, source).unwrap();
+
+        let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+        let compiled = Command::new(rustc)
+            .args(["--edition", "2024", "-o"])
+            .arg(&binary_path)
+            .arg(&source_path)
+            .output()
+            .unwrap();
+        assert!(compiled.status.success(), "{}", String::from_utf8_lossy(&compiled.stderr));
+        let output = Command::new(&binary_path).output().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn generated_lexers_match_the_interpreter() {
+        let spec = compile(SPEC).unwrap();
+        let expected: String = Interpreter::new(&spec, INPUT)
+            .map(|lexeme| {
+                let kind = lexeme
+                    .rule
+                    .map_or_else(|| String::from("Error"), |rule_id| to_variant_name(&spec.rules[rule_id].name));
+                format!("{kind} {}..{}\n", lexeme.span.start, lexeme.span.end)
+            })
+            .collect();
+        for kind in ["Directive", "Hash", "LastWord", "RangeStart", "StringEnd", "Text", "Error"] {
+            assert!(expected.contains(&format!("{kind} ")), "{kind} is not exercised:\n{expected}");
+        }
+
+        for strategy in [Strategy::Table, Strategy::Direct] {
+            assert_eq!(run_generated(strategy), expected, "{strategy:?}");
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::fmt::Write;
+
+use crate::dfa::DEAD_STATE;
+
+use super::RustGenerator;
+
+const VALUES_PER_LINE: usize = 16;
+
+const TABLE_MATCHER: &str = "\
+fn longest_match(bytes: &[u8], start: usize, start_state: usize) -> Option<(Action, usize)> {
+    let mut state = start_state;
+    let mut last_match = None;
+    for (index, &byte) in bytes[start..].iter().enumerate() {
+        let class = BYTE_CLASSES[byte as usize] as usize;
+        state = TRANSITIONS[state * CLASS_COUNT + class] as usize;
+        if state == DEAD_STATE {
+            break;
+        }
+        let end = start + index + 1;
+        let action = if end == bytes.len() || bytes[end] == b'\\n' {
+            LINE_END_ACTIONS[state]
+        } else {
+            ACTIONS[state]
+        };
+        if !matches!(action, Action::None) {
+            last_match = Some((action, end));
+        }
+    }
+    last_match
+}
+";
+
+impl RustGenerator<'_> {
+    pub(super) fn emit_table_matcher(&mut self) {
+        let dfa = self.dfa;
+        let state_type = if dfa.states.len() <= 0x100 {
+            "u8"
+        } else if dfa.states.len() <= 0x10000 {
+            "u16"
+        } else {
+            "u32"
+        };
+
+        let mut output = String::new();
+        let _ = writeln!(output, "const DEAD_STATE: usize = {DEAD_STATE};");
+        let _ = writeln!(output, "const CLASS_COUNT: usize = {};", dfa.class_count);
+        let _ = writeln!(output);
+
+        let byte_classes: Vec<String> = dfa.byte_classes.iter().map(u8::to_string).collect();
+        emit_array(&mut output, "BYTE_CLASSES", "u8", &byte_classes);
+
+        let transitions: Vec<String> = dfa
+            .states
+            .iter()
+            .flat_map(|state| state.transitions.iter().map(usize::to_string))
+            .collect();
+        emit_array(&mut output, "TRANSITIONS", state_type, &transitions);
+
+        let actions: Vec<String> = dfa.states.iter().map(|state| self.action(state.accept)).collect();
+        let _ = writeln!(output, "static ACTIONS: [Action; {}] = [", actions.len());
+        for action in actions {
+            let _ = writeln!(output, "    {action},");
+        }
+        let _ = writeln!(output, "];");
+        let _ = writeln!(output);
+
+        if dfa.states.iter().all(|state| state.line_end_accept == state.accept) {
+            let _ = writeln!(output, "static LINE_END_ACTIONS: &[Action] = &ACTIONS;");
+        } else {
+            let _ = writeln!(output, "static LINE_END_ACTIONS: &[Action] = &[");
+            for state in &dfa.states {
+                let _ = writeln!(output, "    {},", self.action(state.line_end_accept));
+            }
+            let _ = writeln!(output, "];");
+        }
+        let _ = writeln!(output);
+
+        output.push_str(TABLE_MATCHER);
+        self.output.push_str(&output);
+    }
+}
+
+pub(super) fn emit_array(output: &mut String, name: &str, element_type: &str, values: &[String]) {
+    let _ = writeln!(output, "static {name}: [{element_type}; {}] = [", values.len());
+    for chunk in values.chunks(VALUES_PER_LINE) {
+        let _ = writeln!(output, "    {},", chunk.join(", "));
+    }
+    let _ = writeln!(output, "];");
+    let _ = writeln!(output);
+}
@@ -0,0 +1,108 @@
+use std::fmt::Write;
+
+use crate::dfa::{DEAD_STATE, Dfa};
+
+use super::{RustGenerator, table::emit_array};
+
+const CONTEXT_MATCHER: &str = "\
+#[derive(Debug)]
+struct ContextDfa {
+    start: usize,
+    line_start: usize,
+    class_count: usize,
+    byte_classes: &'static [u8; 256],
+    transitions: &'static [u32],
+    accepting: &'static [bool],
+}
+
+impl ContextDfa {
+    fn next_state(&self, state: usize, byte: u8) -> usize {
+        let class = self.byte_classes[byte as usize] as usize;
+        self.transitions[state * self.class_count + class] as usize
+    }
+
+    fn is_match(&self, bytes: &[u8]) -> bool {
+        let state = bytes.iter().fold(self.start, |state, &byte| self.next_state(state, byte));
+        self.accepting[state]
+    }
+}
+
+fn head_end(head: &ContextDfa, tail: &ContextDfa, bytes: &[u8], start: usize, end: usize) -> usize {
+    let mut state = if start == 0 || bytes[start - 1] == b'\\n' {
+        head.line_start
+    } else {
+        head.start
+    };
+    let mut head_end = end;
+    for (index, &byte) in bytes[start..end].iter().enumerate() {
+        state = head.next_state(state, byte);
+        if state == CONTEXT_DEAD_STATE {
+            break;
+        }
+        let split = start + index + 1;
+        if head.accepting[state] && tail.is_match(&bytes[split..end]) {
+            head_end = split;
+        }
+    }
+    head_end
+}
+";
+
+impl RustGenerator<'_> {
+    pub(super) fn emit_trailing_contexts(&mut self) {
+        if self.rules.iter().all(|rule| rule.trailing_context.is_none()) {
+            return;
+        }
+
+        let mut output = String::new();
+        let _ = writeln!(output);
+        let _ = writeln!(output, "const CONTEXT_DEAD_STATE: usize = {DEAD_STATE};");
+        let _ = writeln!(output);
+        output.push_str(CONTEXT_MATCHER);
+
+        for (rule_id, rule) in self.rules.iter().enumerate() {
+            let Some(trailing_context) = &rule.trailing_context else {
+                continue;
+            };
+            let _ = writeln!(output);
+            emit_context_dfa(&mut output, &format!("HEAD_{rule_id}"), &trailing_context.head);
+            emit_context_dfa(&mut output, &format!("TAIL_{rule_id}"), &trailing_context.tail);
+            let _ = writeln!(
+                output,
+                "fn trailing_context_{rule_id}(bytes: &[u8], start: usize, end: usize) -> usize {{"
+            );
+            let _ = writeln!(output, "    head_end(&HEAD_{rule_id}, &TAIL_{rule_id}, bytes, start, end)");
+            let _ = writeln!(output, "}}");
+        }
+        self.output.push_str(&output);
+    }
+}
+
+fn emit_context_dfa(output: &mut String, name: &str, dfa: &Dfa) {
+    let byte_classes: Vec<String> = dfa.byte_classes.iter().map(u8::to_string).collect();
+    emit_array(output, &format!("{name}_BYTE_CLASSES"), "u8", &byte_classes);
+
+    let transitions: Vec<String> = dfa
+        .states
+        .iter()
+        .flat_map(|state| state.transitions.iter().map(usize::to_string))
+        .collect();
+    emit_array(output, &format!("{name}_TRANSITIONS"), "u32", &transitions);
+
+    let accepting: Vec<String> = dfa
+        .states
+        .iter()
+        .map(|state| state.accept.is_some().to_string())
+        .collect();
+    emit_array(output, &format!("{name}_ACCEPTING"), "bool", &accepting);
+
+    let _ = writeln!(output, "static {name}: ContextDfa = ContextDfa {{");
+    let _ = writeln!(output, "    start: {},", dfa.starts[0]);
+    let _ = writeln!(output, "    line_start: {},", dfa.line_starts[0]);
+    let _ = writeln!(output, "    class_count: {},", dfa.class_count);
+    let _ = writeln!(output, "    byte_classes: &{name}_BYTE_CLASSES,");
+    let _ = writeln!(output, "    transitions: &{name}_TRANSITIONS,");
+    let _ = writeln!(output, "    accepting: &{name}_ACCEPTING,");
+    let _ = writeln!(output, "}};");
+    let _ = writeln!(output);
+}
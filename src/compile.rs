@@ -0,0 +1,134 @@
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::{
+    ast::{ASTNode, RuleAction, RuleKind},
+    case_folding,
+    dfa::{Dfa, TrailingContext},
+    error::Error,
+    lexer::Lexer,
+    linter::Linter,
+    nfa::{ModeId, NfaBuilder},
+    parser::Parser,
+    resolver::Resolver,
+    span::Span,
+    warning::Warning,
+};
+
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    pub name: String,
+    pub name_span: Span,
+    pub kind: RuleKind,
+    pub mode: ModeId,
+    pub mode_change: Option<ModeChange>,
+    pub trailing_context: Option<TrailingContext>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeChange {
+    Push(ModeId),
+    Pop,
+    Enter(ModeId),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Statistics {
+    pub nfa_states: usize,
+    pub dfa_states: usize,
+    pub minimized_dfa_states: usize,
+}
+
+#[derive(Debug)]
+pub struct CompiledSpec {
+    pub rules: Vec<CompiledRule>,
+    pub modes: Vec<String>,
+    pub dfa: Dfa,
+    pub statistics: Statistics,
+    pub warnings: Vec<Warning>,
+}
+
+#[derive(Debug, Error)]
+#[error("Failed to compile the specification")]
+pub struct Diagnostics {
+    pub errors: Vec<Error>,
+    pub warnings: Vec<Warning>,
+}
+
+impl Diagnostic for Diagnostics {
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        let errors = self.errors.iter().map(|error| error as &dyn Diagnostic);
+        let warnings = self.warnings.iter().map(|warning| warning as &dyn Diagnostic);
+        Some(Box::new(errors.chain(warnings)))
+    }
+}
+
+pub fn compile(source: &str) -> Result<CompiledSpec, Diagnostics> {
+    let mut lexer = Lexer::new(source);
+    let (tokens, mut errors) = lexer.lex();
+
+    let mut parser = Parser::new(&tokens);
+    let (mut rules, parser_errors, mut warnings) = parser.parse();
+    errors.extend(parser_errors);
+
+    let mut resolver = Resolver::new(&rules);
+    let (graph, modes, resolver_errors) = resolver.resolve();
+    errors.extend(resolver_errors);
+
+    let mut linter = Linter::new(&rules, &graph);
+    warnings.extend(linter.lint());
+
+    if !errors.is_empty() {
+        return Err(Diagnostics { errors, warnings });
+    }
+
+    let unfolded_dfa = case_folding::is_case_insensitive(&rules)
+        .then(|| Dfa::from_nfa(&NfaBuilder::new(&rules, &modes).build()));
+    case_folding::fold_case(&mut rules);
+    let mut nfa_builder = NfaBuilder::new(&rules, &modes);
+    let nfa = nfa_builder.build();
+
+    let mode_id = |name: &str| modes.iter().position(|&mode| mode == name).unwrap_or_default();
+    let rules: Vec<CompiledRule> = rules
+        .iter()
+        .enumerate()
+        .map(|(rule_id, rule)| CompiledRule {
+            name: rule.name.to_string(),
+            name_span: rule.name_span,
+            kind: rule.kind.clone(),
+            mode: mode_id(rule.mode),
+            mode_change: rule.action.map(|action| match action {
+                RuleAction::Push { mode, .. } => ModeChange::Push(mode_id(mode)),
+                RuleAction::Pop { .. } => ModeChange::Pop,
+                RuleAction::Enter { mode, .. } => ModeChange::Enter(mode_id(mode)),
+            }),
+            trailing_context: match &rule.pattern {
+                ASTNode::TrailingContext { head, tail, .. } => Some(TrailingContext {
+                    head: Dfa::from_nfa(&nfa_builder.build_pattern(rule_id, head)).minimize(),
+                    tail: Dfa::from_nfa(&nfa_builder.build_pattern(rule_id, tail)).minimize(),
+                }),
+                _ => None,
+            },
+        })
+        .collect();
+    let modes = modes.iter().map(|mode| mode.to_string()).collect();
+
+    let dfa = Dfa::from_nfa(&nfa);
+    warnings.extend(dfa.find_unreachable_rules(&rules));
+    warnings.extend(dfa.find_ambiguities(&rules, unfolded_dfa.as_ref().unwrap_or(&dfa)));
+    let minimized_dfa = dfa.minimize();
+
+    let statistics = Statistics {
+        nfa_states: nfa.states.len(),
+        dfa_states: dfa.states.len(),
+        minimized_dfa_states: minimized_dfa.states.len(),
+    };
+
+    Ok(CompiledSpec {
+        rules,
+        modes,
+        dfa: minimized_dfa,
+        statistics,
+        warnings,
+    })
+}
@@ -0,0 +1,550 @@
+use std::collections::VecDeque;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast::RuleKind,
+    compile::CompiledRule,
+    nfa::{self, ModeId, Nfa, RuleId},
+    warning::{Warning, WarningKind},
+};
+
+pub type StateId = usize;
+
+pub const DEAD_STATE: StateId = 0;
+
+#[derive(Debug, Clone)]
+pub struct DfaState {
+    pub transitions: Vec<StateId>,
+    pub accept: Option<RuleId>,
+    pub line_end_accept: Option<RuleId>,
+    pub accepts: Vec<RuleId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Dfa {
+    pub states: Vec<DfaState>,
+    pub starts: Vec<StateId>,
+    pub line_starts: Vec<StateId>,
+    pub byte_classes: [u8; 256],
+    pub class_count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrailingContext {
+    pub head: Dfa,
+    pub tail: Dfa,
+}
+
+impl TrailingContext {
+    pub fn head_end(&self, bytes: &[u8], start: usize, end: usize) -> usize {
+        let mut state = self.head.start_state(0, bytes, start);
+        let mut head_end = end;
+        for (index, &byte) in bytes[start..end].iter().enumerate() {
+            state = self.head.next_state(state, byte);
+            if state == DEAD_STATE {
+                break;
+            }
+            let split = start + index + 1;
+            if self.head.states[state].accept.is_some() && self.tail.is_match(&bytes[split..end]) {
+                head_end = split;
+            }
+        }
+        head_end
+    }
+}
+
+impl Dfa {
+    pub fn from_nfa(nfa: &Nfa) -> Self {
+        let (byte_classes, class_count) = compute_byte_classes(nfa);
+        let representatives = class_representatives(&byte_classes, class_count);
+
+        let mut dfa = Dfa {
+            states: Vec::new(),
+            starts: Vec::new(),
+            line_starts: Vec::new(),
+            byte_classes,
+            class_count,
+        };
+        dfa.add_state(&[], nfa);
+
+        let mut state_ids: FxHashMap<Vec<nfa::StateId>, StateId> = FxHashMap::default();
+        let mut queue: VecDeque<Vec<nfa::StateId>> = VecDeque::new();
+        state_ids.insert(Vec::new(), DEAD_STATE);
+
+        for (&nfa_start, &nfa_line_start) in nfa.starts.iter().zip(&nfa.line_starts) {
+            for (nfa_start, is_line_start) in [(nfa_start, false), (nfa_line_start, true)] {
+                let start_set = epsilon_closure(nfa, vec![nfa_start]);
+                let start = match state_ids.get(&start_set) {
+                    Some(&start) => start,
+                    None => {
+                        let start = dfa.add_state(&start_set, nfa);
+                        state_ids.insert(start_set.clone(), start);
+                        queue.push_back(start_set);
+                        start
+                    }
+                };
+                if is_line_start {
+                    dfa.line_starts.push(start);
+                } else {
+                    dfa.starts.push(start);
+                }
+            }
+        }
+
+        while let Some(set) = queue.pop_front() {
+            let from = state_ids[&set];
+            for (class, &byte) in representatives.iter().enumerate() {
+                let mut moved: Vec<nfa::StateId> = Vec::new();
+                for &state in &set {
+                    for &(range, to) in &nfa.states[state].transitions {
+                        if range.start <= byte && byte <= range.end {
+                            moved.push(to);
+                        }
+                    }
+                }
+                let closure = epsilon_closure(nfa, moved);
+
+                let to = match state_ids.get(&closure) {
+                    Some(&to) => to,
+                    None => {
+                        let to = dfa.add_state(&closure, nfa);
+                        state_ids.insert(closure.clone(), to);
+                        queue.push_back(closure);
+                        to
+                    }
+                };
+                dfa.states[from].transitions[class] = to;
+            }
+        }
+        dfa
+    }
+
+    pub fn next_state(&self, state: StateId, byte: u8) -> StateId {
+        let class = self.byte_classes[byte as usize] as usize;
+        self.states[state].transitions[class]
+    }
+
+    pub fn is_match(&self, bytes: &[u8]) -> bool {
+        let state = bytes
+            .iter()
+            .fold(self.starts[0], |state, &byte| self.next_state(state, byte));
+        self.states[state].accept.is_some()
+    }
+
+    pub fn start_state(&self, mode: ModeId, bytes: &[u8], start: usize) -> StateId {
+        if is_line_start(bytes, start) {
+            self.line_starts[mode]
+        } else {
+            self.starts[mode]
+        }
+    }
+
+    pub fn longest_match(&self, mode: ModeId, bytes: &[u8], start: usize) -> Option<(RuleId, usize)> {
+        let mut state = self.start_state(mode, bytes, start);
+        let mut last_match = None;
+        for (index, &byte) in bytes[start..].iter().enumerate() {
+            state = self.next_state(state, byte);
+            if state == DEAD_STATE {
+                break;
+            }
+            let end = start + index + 1;
+            let accept = if is_line_end(bytes, end) {
+                self.states[state].line_end_accept
+            } else {
+                self.states[state].accept
+            };
+            if let Some(rule_id) = accept {
+                last_match = Some((rule_id, end));
+            }
+        }
+        last_match
+    }
+
+    pub fn minimize(&self) -> Dfa {
+        let mut inverse: Vec<Vec<Vec<StateId>>> = vec![vec![Vec::new(); self.states.len()]; self.class_count];
+        for (from, state) in self.states.iter().enumerate() {
+            for (class, &to) in state.transitions.iter().enumerate() {
+                inverse[class][to].push(from);
+            }
+        }
+
+        let mut blocks_by_accept: FxHashMap<(Option<RuleId>, Option<RuleId>), Vec<StateId>> = FxHashMap::default();
+        for (state_id, state) in self.states.iter().enumerate() {
+            blocks_by_accept.entry((state.accept, state.line_end_accept)).or_default().push(state_id);
+        }
+        let mut blocks: Vec<Vec<StateId>> = blocks_by_accept.into_values().collect();
+        blocks.sort_unstable();
+
+        let mut block_of = vec![0; self.states.len()];
+        for (block_id, block) in blocks.iter().enumerate() {
+            for &state in block {
+                block_of[state] = block_id;
+            }
+        }
+
+        let mut worklist: Vec<usize> = (0..blocks.len()).collect();
+        let mut is_in_worklist = vec![true; blocks.len()];
+        while let Some(splitter) = worklist.pop() {
+            is_in_worklist[splitter] = false;
+            let splitter_states = blocks[splitter].clone();
+            for class_inverse in &inverse {
+                let mut predecessors: FxHashMap<usize, Vec<StateId>> = FxHashMap::default();
+                for &to in &splitter_states {
+                    for &from in &class_inverse[to] {
+                        predecessors.entry(block_of[from]).or_default().push(from);
+                    }
+                }
+
+                for (block_id, mut inside) in predecessors {
+                    inside.sort_unstable();
+                    inside.dedup();
+                    if inside.len() == blocks[block_id].len() {
+                        continue;
+                    }
+
+                    let outside: Vec<StateId> = blocks[block_id]
+                        .iter()
+                        .copied()
+                        .filter(|state| inside.binary_search(state).is_err())
+                        .collect();
+                    let new_block_id = blocks.len();
+                    for &state in &inside {
+                        block_of[state] = new_block_id;
+                    }
+                    let is_inside_smaller = inside.len() <= outside.len();
+                    blocks[block_id] = outside;
+                    blocks.push(inside);
+                    is_in_worklist.push(false);
+
+                    if is_in_worklist[block_id] || is_inside_smaller {
+                        worklist.push(new_block_id);
+                        is_in_worklist[new_block_id] = true;
+                    } else {
+                        worklist.push(block_id);
+                        is_in_worklist[block_id] = true;
+                    }
+                }
+            }
+        }
+
+        let mut order: Vec<usize> = (0..blocks.len()).collect();
+        order.sort_unstable_by_key(|&block_id| blocks[block_id].iter().min().copied());
+        let mut new_ids = vec![DEAD_STATE; blocks.len()];
+        for (new_id, &block_id) in order.iter().enumerate() {
+            new_ids[block_id] = new_id;
+        }
+
+        let states = order
+            .iter()
+            .map(|&block_id| {
+                let representative = &self.states[blocks[block_id][0]];
+                DfaState {
+                    transitions: representative
+                        .transitions
+                        .iter()
+                        .map(|&to| new_ids[block_of[to]])
+                        .collect(),
+                    accept: representative.accept,
+                    line_end_accept: representative.line_end_accept,
+                    accepts: representative.accept.into_iter().chain(representative.line_end_accept).collect(),
+                }
+            })
+            .collect();
+
+        Dfa {
+            states,
+            starts: self.starts.iter().map(|&start| new_ids[block_of[start]]).collect(),
+            line_starts: self.line_starts.iter().map(|&start| new_ids[block_of[start]]).collect(),
+            byte_classes: self.byte_classes,
+            class_count: self.class_count,
+        }
+    }
+
+    pub fn find_unreachable_rules(&self, rules: &[CompiledRule]) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        for (rule_id, rule) in rules.iter().enumerate() {
+            if !matches!(rule.kind, RuleKind::Token) {
+                continue;
+            }
+            let Some(shadowing_rules) = self.shadowing_rules(rule_id) else {
+                continue;
+            };
+
+            let names: Vec<String> = shadowing_rules
+                .iter()
+                .map(|&shadowing_id| format!("'{}'", rules[shadowing_id].name))
+                .collect();
+            let shadowing_spans = shadowing_rules
+                .iter()
+                .map(|&shadowing_id| rules[shadowing_id].name_span.to_source_span())
+                .collect();
+
+            warnings.push(Warning {
+                kind: WarningKind::UnreachableRule {
+                    name: rule.name.clone(),
+                    shadowed_by: names.join(", "),
+                    shadowing_spans,
+                },
+                span: rule.name_span.to_source_span(),
+            });
+        }
+        warnings
+    }
+
+    fn shadowing_rules(&self, rule_id: RuleId) -> Option<Vec<RuleId>> {
+        let mut shadowing_rules: Vec<RuleId> = Vec::new();
+        for state in &self.states {
+            if !state.accepts.contains(&rule_id) {
+                continue;
+            }
+            if state.accept == Some(rule_id) || state.line_end_accept == Some(rule_id) {
+                return None;
+            }
+            shadowing_rules.extend(state.accept);
+            shadowing_rules.extend(state.line_end_accept);
+        }
+
+        shadowing_rules.sort_unstable();
+        shadowing_rules.dedup();
+        if shadowing_rules.is_empty() {
+            return None;
+        }
+        Some(shadowing_rules)
+    }
+
+    pub fn find_ambiguities(&self, rules: &[CompiledRule], unfolded: &Dfa) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        let examples = self.shortest_examples();
+        let is_candidate: Vec<bool> = rules
+            .iter()
+            .enumerate()
+            .map(|(rule_id, rule)| matches!(rule.kind, RuleKind::Token) && self.shadowing_rules(rule_id).is_none())
+            .collect();
+
+        for (winner, winner_rule) in rules.iter().enumerate() {
+            if !is_candidate[winner] {
+                continue;
+            }
+            for (loser, loser_rule) in rules.iter().enumerate().skip(winner + 1) {
+                if !is_candidate[loser] {
+                    continue;
+                }
+
+                let overlap = self
+                    .states
+                    .iter()
+                    .position(|state| state.accepts.contains(&winner) && state.accepts.contains(&loser));
+                let Some(state_id) = overlap else {
+                    continue;
+                };
+                let is_winner_subset = unfolded
+                    .states
+                    .iter()
+                    .all(|state| !state.accepts.contains(&winner) || state.accepts.contains(&loser));
+                if is_winner_subset {
+                    continue;
+                }
+
+                warnings.push(Warning {
+                    kind: WarningKind::AmbiguousRules {
+                        winner: winner_rule.name.clone(),
+                        winner_span: winner_rule.name_span.to_source_span(),
+                        loser: loser_rule.name.clone(),
+                        loser_span: loser_rule.name_span.to_source_span(),
+                        example: examples[state_id].clone().unwrap_or_default(),
+                    },
+                    span: loser_rule.name_span.to_source_span(),
+                });
+            }
+        }
+        warnings
+    }
+
+    fn shortest_examples(&self) -> Vec<Option<String>> {
+        let representatives = class_representatives(&self.byte_classes, self.class_count);
+        let mut paths: Vec<Option<Vec<u8>>> = vec![None; self.states.len()];
+        let mut queue = VecDeque::new();
+        for &start in self.starts.iter().chain(&self.line_starts) {
+            if paths[start].is_none() {
+                paths[start] = Some(Vec::new());
+                queue.push_back(start);
+            }
+        }
+
+        while let Some(state) = queue.pop_front() {
+            for (class, &to) in self.states[state].transitions.iter().enumerate() {
+                if to == DEAD_STATE || paths[to].is_some() {
+                    continue;
+                }
+                let mut path = paths[state].clone().unwrap_or_default();
+                path.push(representatives[class]);
+                paths[to] = Some(path);
+                queue.push_back(to);
+            }
+        }
+
+        paths
+            .into_iter()
+            .map(|path| path.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+            .collect()
+    }
+
+    fn add_state(&mut self, set: &[nfa::StateId], nfa: &Nfa) -> StateId {
+        let accept = set.iter().filter_map(|&state| nfa.states[state].accept).min();
+        let mut accepts: Vec<RuleId> = set
+            .iter()
+            .flat_map(|&state| [nfa.states[state].accept, nfa.states[state].line_end_accept])
+            .flatten()
+            .collect();
+        accepts.sort_unstable();
+        accepts.dedup();
+
+        self.states.push(DfaState {
+            transitions: vec![DEAD_STATE; self.class_count],
+            accept,
+            line_end_accept: accepts.first().copied(),
+            accepts,
+        });
+        self.states.len() - 1
+    }
+}
+
+fn is_line_start(bytes: &[u8], offset: usize) -> bool {
+    offset == 0 || bytes[offset - 1] == b'\n'
+}
+
+fn is_line_end(bytes: &[u8], offset: usize) -> bool {
+    offset == bytes.len() || bytes[offset] == b'\n'
+}
+
+fn epsilon_closure(nfa: &Nfa, mut stack: Vec<nfa::StateId>) -> Vec<nfa::StateId> {
+    let mut closure: Vec<nfa::StateId> = Vec::new();
+    let mut visited = vec![false; nfa.states.len()];
+    while let Some(state) = stack.pop() {
+        if visited[state] {
+            continue;
+        }
+        visited[state] = true;
+        closure.push(state);
+        stack.extend(nfa.states[state].epsilons.iter().copied());
+    }
+    closure.sort_unstable();
+    closure
+}
+
+fn compute_byte_classes(nfa: &Nfa) -> ([u8; 256], usize) {
+    let mut boundaries = [false; 257];
+    for state in &nfa.states {
+        for (range, _) in &state.transitions {
+            boundaries[range.start as usize] = true;
+            boundaries[range.end as usize + 1] = true;
+        }
+    }
+
+    let mut byte_classes = [0; 256];
+    let mut class = 0;
+    for byte in 1..256 {
+        if boundaries[byte] {
+            class += 1;
+        }
+        byte_classes[byte] = class as u8;
+    }
+    (byte_classes, class + 1)
+}
+
+fn class_representatives(byte_classes: &[u8; 256], class_count: usize) -> Vec<u8> {
+    let mut representatives: Vec<Option<u8>> = vec![None; class_count];
+    for byte in (0..=255u8).rev() {
+        let representative = &mut representatives[byte_classes[byte as usize] as usize];
+        let is_readable = representative.is_some_and(|current| current.is_ascii_alphanumeric());
+        if !is_readable || byte.is_ascii_alphanumeric() {
+            *representative = Some(byte);
+        }
+    }
+    representatives.into_iter().map(|byte| byte.unwrap_or(0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{case_folding, compile, lexer::Lexer, nfa::NfaBuilder, parser::Parser, resolver::Resolver};
+
+    use super::Dfa;
+
+    const INPUT_CHARS: [char; 5] = ['a', 'b', 'c', 'é', '\n'];
+
+    fn build_dfa(source: &str) -> Option<Dfa> {
+        let (tokens, lexer_errors) = Lexer::new(source).lex();
+        let (mut rules, parser_errors, _) = Parser::new(&tokens).parse();
+        let (_, modes, resolver_errors) = Resolver::new(&rules).resolve();
+        if !lexer_errors.is_empty() || !parser_errors.is_empty() || !resolver_errors.is_empty() {
+            return None;
+        }
+        case_folding::fold_case(&mut rules);
+        Some(Dfa::from_nfa(&NfaBuilder::new(&rules, &modes).build()))
+    }
+
+    fn assert_same_matches(source: &str, input: &str) {
+        let Some(dfa) = build_dfa(source) else {
+            return;
+        };
+        let minimized = dfa.minimize();
+        for (start, _) in input.char_indices() {
+            assert_eq!(
+                dfa.longest_match(0, input.as_bytes(), start),
+                minimized.longest_match(0, input.as_bytes(), start),
+                "spec:\n{source}\ninput: {input:?} at {start}"
+            );
+        }
+    }
+
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, bound: usize) -> usize {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((self.0 >> 33) % bound as u64) as usize
+        }
+    }
+
+    fn random_pattern(rng: &mut Rng, depth: usize) -> String {
+        let choice = if depth == 0 { 0 } else { rng.below(5) };
+        match choice {
+            0 => ["a", "b", "[ab]", "[^a\\n]", "c"][rng.below(5)].to_string(),
+            1 => format!("{}{}", random_pattern(rng, depth - 1), random_pattern(rng, depth - 1)),
+            2 => format!("({}|{})", random_pattern(rng, depth - 1), random_pattern(rng, depth - 1)),
+            3 => format!("({})+", random_pattern(rng, depth - 1)),
+            _ => format!("({}){{1,3}}", random_pattern(rng, depth - 1)),
+        }
+    }
+
+    #[test]
+    fn minimize_preserves_matches_after_splitting_the_splitter() {
+        let source = "r0 = (([^a\\n])+){1,3}\nr1 = ([^a\\n]|([ab][^a\\n]){1,3})\nr2 = (([^a\\n][^a\\n]|([ab])+))+\n";
+        assert_same_matches(source, "écbcé");
+    }
+
+    #[test]
+    fn minimize_preserves_matches_on_random_specs() {
+        let mut rng = Rng(0x5eed);
+        for _ in 0..500 {
+            let source: String = (0..1 + rng.below(3))
+                .map(|rule_id| format!("r{rule_id} = {}\n", random_pattern(&mut rng, 3)))
+                .collect();
+            let input: String = (0..8).map(|_| INPUT_CHARS[rng.below(INPUT_CHARS.len())]).collect();
+            assert_same_matches(&source, &input);
+        }
+    }
+
+    #[test]
+    fn case_insensitive_keywords_before_identifiers_are_not_ambiguous() {
+        let source = "nocase kick = kick\nnocase select = select\nident = [a-zA-Z_][a-zA-Z0-9_]*\n";
+        assert!(compile(source).unwrap().warnings.is_empty());
+    }
+
+    #[test]
+    fn overlapping_rules_are_ambiguous() {
+        let spec = compile("hex = [0-9a-f]+\nident = [a-z]+\n").unwrap();
+        assert_eq!(spec.warnings.len(), 1);
+    }
+}
@@ -11,7 +11,7 @@ pub struct Error {
     pub span: Span,
 }
 
-#[derive(Debug, Error, Diagnostic)]
+#[derive(Debug, PartialEq, Eq, Error, Diagnostic)]
 pub enum ErrorKind {
     #[error("Invalid Unicode Scalar")]
     #[diagnostic(help("The scalar value '{value}' does not correspond to a valid UTF-8 character"))]
@@ -21,10 +21,22 @@ pub enum ErrorKind {
     #[diagnostic(help("The escape '\\{char}' must be followed by {expected_digits} digits"))]
     InvalidHexEscape { char: char, expected_digits: usize },
 
+    #[error("Invalid Unicode Class")]
+    #[diagnostic(help("The escape '\\{char}' must be followed by a property name, e.g. '\\{char}{{L}}' or '\\{char}{{Script=Greek}}'"))]
+    InvalidUnicodeClass { char: char },
+
+    #[error("Unknown Unicode Property")]
+    #[diagnostic(help("'{name}' is not a supported general category, script or binary property"))]
+    UnknownUnicodeProperty { name: String },
+
     #[error("Invalid Escape Character")]
     #[diagnostic(help("The escape '\\{char}' is not a valid escape character"))]
     InvalidEscapeCharacter { char: char},
 
+    #[error("Dangling Escape")]
+    #[diagnostic(help("The '\\' at the end of the line escapes nothing, only rules outside strings and brackets can continue onto the next line"))]
+    DanglingEscape,
+
     #[error("Literal Tab Character")]
     #[diagnostic(help("Replace the literal tab character with '\\t'"))]
     LiteralTabCharacter,
@@ -33,8 +45,128 @@ pub enum ErrorKind {
     #[diagnostic(help("The value is too large, please pick a smaller value"))]
     IntegerOverflow,
 
+    #[error("Unterminated Comment")]
+    #[diagnostic(help("The block comment is never closed, please add a matching '*/'"))]
+    UnterminatedComment,
+
+    #[error("Unterminated String")]
+    #[diagnostic(help("The string literal is never closed, please add the matching closing quote"))]
+    UnterminatedString,
+
+    #[error("Empty String")]
+    #[diagnostic(help("A string literal must contain at least one character"))]
+    EmptyString,
+
     #[error("Unexpected End-of-File")]
     UnexpectedEOF,
+
+    #[error("Expected Mode Name")]
+    #[diagnostic(help("Each mode must have a name, e.g. 'mode string' or 'push(string)'"))]
+    ExpectedModeName,
+
+    #[error("Expected Rule Action")]
+    #[diagnostic(help("A rule action must be one of 'push(mode)', 'pop' or 'enter(mode)'"))]
+    ExpectedRuleAction,
+
+    #[error("Undefined Mode")]
+    #[diagnostic(help("The mode '{name}' has no rules, please declare it with 'mode {name}' followed by its rules"))]
+    UndefinedMode { name: String },
+
+    #[error("Helper Rule Action")]
+    #[diagnostic(help("Helper rules never produce tokens, please move the action to the rules referencing it"))]
+    HelperRuleAction,
+
+    #[error("Expected Rule Name")]
+    #[diagnostic(help("Each rule must start with a name, e.g. 'digit = [0-9]'"))]
+    ExpectedRuleName,
+
+    #[error("Expected '='")]
+    #[diagnostic(help("The rule name must be followed by '=' and a pattern"))]
+    ExpectedEquals,
+
+    #[error("Expected Expression")]
+    #[diagnostic(help("A pattern was expected here"))]
+    ExpectedExpression,
+
+    #[error("Unexpected Token")]
+    #[diagnostic(help("This token is not valid here, consider escaping it with '\\'"))]
+    UnexpectedToken,
+
+    #[error("Expected ')'")]
+    #[diagnostic(help("The group is never closed"))]
+    ExpectedClosedParen,
+
+    #[error("Unclosed '('")]
+    #[diagnostic(help("The group is never closed, please add a matching ')' before the end of the rule"))]
+    UnclosedParen { open_span: Span },
+
+    #[error("Unclosed '['")]
+    #[diagnostic(help("The character class is never closed, please add a matching ']' before the end of the rule"))]
+    UnclosedBracket { open_span: Span },
+
+    #[error("Unclosed '{{'")]
+    #[diagnostic(help("The braces are never closed, please add a matching '}}' before the end of the rule"))]
+    UnclosedBrace { open_span: Span },
+
+    #[error("Empty Character Class")]
+    #[diagnostic(help("A character class must contain at least one character"))]
+    EmptyCharClass,
+
+    #[error("Invalid Character Range")]
+    #[diagnostic(help("The start '{min}' is greater than the end '{max}'"))]
+    InvalidCharRange { min: char, max: char },
+
+    #[error("Undefined Reference")]
+    #[diagnostic(help("No rule named '{name}' is defined"))]
+    UndefinedReference { name: String },
+
+    #[error("Recursive Reference")]
+    #[diagnostic(help("The rule '{name}' refers to itself through {cycle}, which cannot be expressed as a regular language"))]
+    RecursiveReference { name: String, cycle: String },
+
+    #[error("Duplicate Rule")]
+    #[diagnostic(help("The rule '{name}' is already defined, please rename or remove one of them"))]
+    DuplicateRule { name: String, first_span: Span },
+
+    #[error("Reserved Token Name")]
+    #[diagnostic(help("The token variant '{variant}' is reserved by the generated lexer, please rename this rule"))]
+    ReservedTokenName { variant: String },
+
+    #[error("Token Name Collision")]
+    #[diagnostic(help("Both rules generate the token variant '{variant}', please rename one of them"))]
+    TokenNameCollision { variant: String, first_span: Span },
+
+    #[error("Invalid Reference")]
+    #[diagnostic(help("A reference may only contain a rule name, e.g. '{{{name}}}', put any repetition after it, e.g. '{{{name}}}{{2,3}}'"))]
+    InvalidReference { name: String },
+
+    #[error("Expected Number")]
+    #[diagnostic(help("Repetition bounds must be numbers, e.g. '{{2,3}}', a reference to another rule is written as '{{name}}'"))]
+    ExpectedNumber,
+
+    #[error("Invalid Repetition Range")]
+    #[diagnostic(help("The minimum {min} is greater than the maximum {max}"))]
+    InvalidRepetitionRange { min: usize, max: usize },
+
+    #[error("Repetition Too Large")]
+    #[diagnostic(help("The repetition expands its pattern {value} times, counting nested repetitions and referenced rules, which exceeds the maximum of {limit}, consider using '+' or '*' instead"))]
+    RepetitionTooLarge { value: usize, limit: usize },
+
+    #[error("Invalid Group Flags")]
+    #[diagnostic(help("The only supported group flag is '(?i:...)' for case-insensitive matching"))]
+    InvalidGroupFlags,
+
+    #[error("Misplaced Anchor")]
+    #[diagnostic(help("'^' may only start and '$' may only end a token or skip rule, and '$' cannot be combined with trailing context"))]
+    MisplacedAnchor,
+
+    #[error("Misplaced Trailing Context")]
+    #[diagnostic(help("The trailing context operator '/' may only appear once, at the top level of a token or skip rule"))]
+    MisplacedTrailingContext,
+
+    #[error("Nullable Rule")]
+    #[diagnostic(help("The rule '{name}' matches the empty string, which would produce empty tokens, make sure it always consumes at least one character"))]
+    NullableRule { name: String, empty_spans: Vec<Span> },
 }
 
 impl Diagnostic for Error {
@@ -52,6 +184,46 @@ impl Diagnostic for Error {
     }
     fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
         match self.kind {
+            ErrorKind::DuplicateRule { first_span, .. } => {
+                let text1 = String::from("first defined here");
+                let label1 = LabeledSpan::new_with_span(Some(text1), first_span.to_source_span());
+
+                let text2 = String::from("redefined here");
+                let label2 = LabeledSpan::new_with_span(Some(text2), self.span.to_source_span());
+                Some(Box::new(std::iter::once(label1).chain(std::iter::once(label2))))
+            }
+
+            ErrorKind::TokenNameCollision { first_span, .. } => {
+                let text1 = String::from("first defined here");
+                let label1 = LabeledSpan::new_with_span(Some(text1), first_span.to_source_span());
+
+                let text2 = String::from("collides with this rule");
+                let label2 = LabeledSpan::new_with_span(Some(text2), self.span.to_source_span());
+                Some(Box::new(std::iter::once(label1).chain(std::iter::once(label2))))
+            }
+
+            ErrorKind::UnclosedParen { open_span }
+            | ErrorKind::UnclosedBracket { open_span }
+            | ErrorKind::UnclosedBrace { open_span } => {
+                let text1 = String::from("opened here");
+                let label1 = LabeledSpan::new_with_span(Some(text1), open_span.to_source_span());
+
+                let text2 = String::from("rule ends here");
+                let label2 = LabeledSpan::new_with_span(Some(text2), self.span.to_source_span());
+                Some(Box::new(std::iter::once(label1).chain(std::iter::once(label2))))
+            }
+
+            ErrorKind::NullableRule { ref empty_spans, .. } => {
+                let text = String::from("in this rule");
+                let label = LabeledSpan::new_with_span(Some(text), self.span.to_source_span());
+
+                let empty_labels = empty_spans.iter().map(|span| {
+                    let text = String::from("matches the empty string");
+                    LabeledSpan::new_with_span(Some(text), span.to_source_span())
+                });
+                Some(Box::new(std::iter::once(label).chain(empty_labels)))
+            }
+
             _ => {
                 let text = String::from("here");
                 let label = LabeledSpan::new_with_span(Some(text), self.span.to_source_span());
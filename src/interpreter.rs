@@ -0,0 +1,178 @@
+use crate::{
+    ast::RuleKind,
+    compile::{CompiledSpec, ModeChange},
+    nfa::{ModeId, RuleId},
+    span::Span,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lexeme {
+    pub rule: Option<RuleId>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Interpreter<'a> {
+    spec: &'a CompiledSpec,
+    input: &'a str,
+    offset: usize,
+    modes: Vec<ModeId>,
+}
+
+impl Iterator for Interpreter<'_> {
+    type Item = Lexeme;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.offset;
+            if start >= self.input.len() {
+                return None;
+            }
+
+            let mode = self.mode();
+            let Some((rule_id, end)) = self.spec.dfa.longest_match(mode, self.input.as_bytes(), start) else {
+                let len = self.input[start..].chars().next().map_or(1, char::len_utf8);
+                self.offset = start + len;
+                return Some(Lexeme {
+                    rule: None,
+                    span: Span::from((start, self.offset)),
+                });
+            };
+
+            let end = match &self.spec.rules[rule_id].trailing_context {
+                Some(trailing_context) => trailing_context.head_end(self.input.as_bytes(), start, end),
+                None => end,
+            };
+            self.offset = end;
+            if let Some(mode_change) = self.spec.rules[rule_id].mode_change {
+                self.change_mode(mode_change);
+            }
+            if matches!(self.spec.rules[rule_id].kind, RuleKind::Skip) {
+                continue;
+            }
+            return Some(Lexeme {
+                rule: Some(rule_id),
+                span: Span::from((start, end)),
+            });
+        }
+    }
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(spec: &'a CompiledSpec, input: &'a str) -> Self {
+        Self {
+            spec,
+            input,
+            offset: 0,
+            modes: vec![0],
+        }
+    }
+
+    pub fn mode(&self) -> ModeId {
+        self.modes.last().copied().unwrap_or_default()
+    }
+
+    pub fn slice(&self, lexeme: &Lexeme) -> &'a str {
+        &self.input[lexeme.span.start..lexeme.span.end]
+    }
+
+    fn change_mode(&mut self, mode_change: ModeChange) {
+        match mode_change {
+            ModeChange::Push(mode) => self.modes.push(mode),
+            ModeChange::Pop => {
+                if self.modes.len() > 1 {
+                    self.modes.pop();
+                }
+            }
+            ModeChange::Enter(mode) => {
+                if let Some(top) = self.modes.last_mut() {
+                    *top = mode;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile;
+
+    use super::Interpreter;
+
+    fn lex(source: &str, input: &str) -> Vec<(String, String)> {
+        let spec = compile(source).unwrap();
+        let interpreter = Interpreter::new(&spec, input);
+        interpreter
+            .clone()
+            .map(|lexeme| {
+                let name = lexeme.rule.map_or("<error>", |rule_id| &spec.rules[rule_id].name);
+                (name.to_string(), interpreter.slice(&lexeme).to_string())
+            })
+            .collect()
+    }
+
+    fn expected(lexemes: &[(&str, &str)]) -> Vec<(String, String)> {
+        lexemes.iter().map(|&(name, text)| (name.to_string(), text.to_string())).collect()
+    }
+
+    #[test]
+    fn mode_actions_push_pop_and_enter() {
+        let source = r"open -> push(block) = \{
+word = [a-z]+
+raw -> enter(verbatim) = <<
+skip space = [ ]+
+
+mode block
+close -> pop = \}
+nested -> push(block) = \{
+digit = [0-9]
+
+mode verbatim
+raw_end -> enter(initial) = >>
+text = [^>]+
+";
+        assert_eq!(
+            lex(source, "a{1{2}3}b<<x }>>c}"),
+            expected(&[
+                ("word", "a"),
+                ("open", "{"),
+                ("digit", "1"),
+                ("nested", "{"),
+                ("digit", "2"),
+                ("close", "}"),
+                ("digit", "3"),
+                ("close", "}"),
+                ("word", "b"),
+                ("raw", "<<"),
+                ("text", "x }"),
+                ("raw_end", ">>"),
+                ("word", "c"),
+                ("<error>", "}"),
+            ])
+        );
+    }
+
+    #[test]
+    fn trailing_context_ends_the_token_at_the_head() {
+        let source = r"range_start = \d+/\.\.
+int = \d+
+dot_dot = \.\.
+prefix = a+/a*b
+b = b
+skip space = [ ]+
+";
+        assert_eq!(
+            lex(source, "12..3 1.2 aaab"),
+            expected(&[
+                ("range_start", "12"),
+                ("dot_dot", ".."),
+                ("int", "3"),
+                ("int", "1"),
+                ("<error>", "."),
+                ("int", "2"),
+                ("prefix", "aaa"),
+                ("b", "b"),
+            ])
+        );
+    }
+}
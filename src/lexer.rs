@@ -20,6 +20,7 @@ pub struct Lexer<'src> {
     is_in_braces: bool,
     is_in_brackets: bool,
     is_expecting_expr: bool,
+    quote: Option<char>,
     errors: Vec<Error>,
 }
 
@@ -28,6 +29,12 @@ impl<'src> Iterator for Lexer<'src> {
 
     fn next(&mut self) -> Option<Self::Item> {
         unsafe {
+            if let Some(quote) = self.quote
+                && self.peek().is_some_and(|&c| c != '\n')
+            {
+                return Some(self.tokenize_quoted(quote));
+            }
+
             self.skip_whitespace();
             let span_start = self.offset;
             let peeked = *self.peek()?;
@@ -48,12 +55,15 @@ impl<'src> Iterator for Lexer<'src> {
                     self.consume();
                     TokenKind::QMark
                 }
-                '{' => {
+                '{' if self.is_expecting_expr && !self.is_in_brackets && self.is_reference_ahead() => {
+                    return Some(self.tokenize_reference());
+                }
+                '{' if !self.is_in_brackets => {
                     self.consume();
                     self.is_in_braces = true;
                     TokenKind::OpenBrace
                 }
-                '}' => {
+                '}' if !self.is_in_brackets => {
                     self.consume();
                     self.is_in_braces = false;
                     TokenKind::ClosedBrace
@@ -80,7 +90,20 @@ impl<'src> Iterator for Lexer<'src> {
                     self.consume();
                     TokenKind::Dollar
                 }
-                '^' if self.is_in_brackets => {
+                '/' if !self.is_in_brackets && matches!(self.bytes.get(self.offset + 1), Some(b'/' | b'*')) => {
+                    self.consume_comment();
+                    TokenKind::Comment
+                }
+                '/' if self.is_expecting_expr && !self.is_in_brackets => {
+                    self.consume();
+                    TokenKind::Slash
+                }
+                '"' | '\'' if self.is_expecting_expr && !self.is_in_brackets => {
+                    self.consume();
+                    self.quote = Some(peeked);
+                    TokenKind::Quote
+                }
+                '^' if self.is_in_brackets || self.is_expecting_expr => {
                     self.consume();
                     TokenKind::Caret
                 }
@@ -92,6 +115,11 @@ impl<'src> Iterator for Lexer<'src> {
                     self.consume();
                     TokenKind::Comma
                 }
+                '-' if !self.is_expecting_expr && self.bytes.get(self.offset + 1) == Some(&b'>') => {
+                    self.consume();
+                    self.consume();
+                    TokenKind::Arrow
+                }
                 '=' if !self.is_expecting_expr => {
                     self.consume();
                     self.is_expecting_expr = true;
@@ -100,12 +128,13 @@ impl<'src> Iterator for Lexer<'src> {
 
                 ch if !self.is_expecting_expr && (ch.is_alphabetic() || ch == '_') => {
                     let name = self.consume_identifier();
-                    TokenKind::Identifier { name }
-                }
-
-                ch if self.is_in_braces && ch.is_alphabetic() => {
-                    let name = self.consume_identifier();
-                    TokenKind::Identifier { name }
+                    match name {
+                        "helper" if self.is_identifier_ahead() => TokenKind::Helper,
+                        "skip" if self.is_identifier_ahead() => TokenKind::Skip,
+                        "mode" if self.is_identifier_ahead() => TokenKind::Mode,
+                        "nocase" if self.is_identifier_ahead() => TokenKind::NoCase,
+                        _ => TokenKind::Identifier { name },
+                    }
                 }
 
                 ch if self.is_in_braces && ch.is_ascii_digit() => {
@@ -132,6 +161,13 @@ impl<'src> Iterator for Lexer<'src> {
                     });
                 }
 
+                '\n' if self.is_continuation_allowed() && self.is_alternative_ahead() => {
+                    while self.peek().is_some_and(|&c| c.is_whitespace()) {
+                        self.consume();
+                    }
+                    return self.next();
+                }
+
                 '\n' => {
                     while self.peek().is_some_and(|&c| c.is_whitespace()) {
                         self.consume();
@@ -139,9 +175,19 @@ impl<'src> Iterator for Lexer<'src> {
                     self.is_in_braces = false;
                     self.is_in_brackets = false;
                     self.is_expecting_expr = false;
+                    self.quote = None;
                     TokenKind::Newline
                 }
 
+                '\\' if self.is_continuation_allowed() && self.is_line_break_ahead() => {
+                    self.consume();
+                    while self.peek().is_some_and(|&c| c != '\n') {
+                        self.consume();
+                    }
+                    self.consume();
+                    return self.next();
+                }
+
                 '\\' => return Some(self.tokenize_escape()),
 
                 '.' => {
@@ -173,6 +219,7 @@ impl<'src> Lexer<'src> {
             is_in_braces: false,
             is_in_brackets: false,
             is_expecting_expr: false,
+            quote: None,
             errors: Vec::new(),
         }
     }
@@ -187,6 +234,98 @@ impl<'src> Lexer<'src> {
         (tokens, errors)
     }
 
+    unsafe fn tokenize_quoted(&mut self, quote: char) -> Token<'src> {
+        unsafe {
+            let span_start = self.offset;
+            let char = *self.peek().unwrap_unchecked();
+            let token_kind = match char {
+                '\\' if self.bytes.get(self.offset + 1).is_some_and(|&byte| is_class_escape(byte as char)) => {
+                    self.consume();
+                    let escaped = self.consume().unwrap_unchecked();
+                    let span = Span::from((span_start, self.offset));
+                    self.errors.push(Error {
+                        kind: ErrorKind::InvalidEscapeCharacter { char: escaped },
+                        span,
+                    });
+                    return Token {
+                        kind: TokenKind::Error,
+                        span,
+                    };
+                }
+                '\\' => return self.tokenize_escape(),
+                '\t' => {
+                    self.consume();
+                    let span = Span::from((span_start, self.offset));
+                    self.errors.push(Error {
+                        kind: ErrorKind::LiteralTabCharacter,
+                        span,
+                    });
+                    return Token {
+                        kind: TokenKind::Error,
+                        span,
+                    };
+                }
+                char if char == quote => {
+                    self.consume();
+                    self.quote = None;
+                    TokenKind::Quote
+                }
+                char => {
+                    self.consume();
+                    TokenKind::Literal { char }
+                }
+            };
+
+            let span_end = self.offset;
+            Token {
+                kind: token_kind,
+                span: Span::from((span_start, span_end)),
+            }
+        }
+    }
+
+    unsafe fn tokenize_reference(&mut self) -> Token<'src> {
+        unsafe {
+            let span_start = self.offset;
+            self.consume();
+            self.skip_whitespace();
+            let name = self.consume_identifier();
+            self.skip_whitespace();
+
+            if self.peek() == Some(&'}') {
+                self.consume();
+                return Token {
+                    kind: TokenKind::Reference { name },
+                    span: Span::from((span_start, self.offset)),
+                };
+            }
+
+            while self.peek().is_some_and(|&c| c != '}' && c != '\n') {
+                self.consume();
+            }
+            let error = if self.peek() == Some(&'}') {
+                self.consume();
+                Error {
+                    kind: ErrorKind::InvalidReference { name: name.to_string() },
+                    span: Span::from((span_start, self.offset)),
+                }
+            } else {
+                Error {
+                    kind: ErrorKind::UnclosedBrace {
+                        open_span: Span::from((span_start, span_start + 1)),
+                    },
+                    span: Span::from((self.offset, self.offset)),
+                }
+            };
+            let span = error.span;
+            self.errors.push(error);
+            Token {
+                kind: TokenKind::Error,
+                span,
+            }
+        }
+    }
+
     unsafe fn tokenize_escape(&mut self) -> Token<'src> {
         unsafe {
             let span_start = self.offset;
@@ -205,9 +344,17 @@ impl<'src> Lexer<'src> {
             };
 
             let token_kind = match escaped {
+                '\n' => {
+                    self.errors.push(Error {
+                        kind: ErrorKind::DanglingEscape,
+                        span: Span::from((span_start, self.offset)),
+                    });
+                    TokenKind::Error
+                }
                 'x' => return self.tokenize_unicode_escape('x', 2),
                 'u' => return self.tokenize_unicode_escape('u', 4),
                 'U' => return self.tokenize_unicode_escape('U', 8),
+                'p' | 'P' => return self.tokenize_unicode_class(escaped),
 
                 char if is_escaped_literal(char) => {
                     self.consume();
@@ -283,6 +430,57 @@ impl<'src> Lexer<'src> {
         }
     }
 
+    unsafe fn tokenize_unicode_class(&mut self, escape_char: char) -> Token<'src> {
+        unsafe {
+            let span_start = self.offset - '\\'.len_utf8();
+            self.consume();
+
+            let name_range = match self.peek().copied() {
+                Some('{') => {
+                    self.consume();
+                    let name_start = self.offset;
+                    while self.peek().is_some_and(|&c| c != '}' && c != '\n') {
+                        self.consume();
+                    }
+                    let name_end = self.offset;
+                    if self.peek() == Some(&'}') {
+                        self.consume();
+                        Some((name_start, name_end))
+                    } else {
+                        None
+                    }
+                }
+                Some(c) if c.is_alphabetic() => {
+                    let name_start = self.offset;
+                    self.consume();
+                    Some((name_start, self.offset))
+                }
+                _ => None,
+            };
+
+            let span = Span::from((span_start, self.offset));
+            let Some((name_start, name_end)) = name_range else {
+                self.errors.push(Error {
+                    kind: ErrorKind::InvalidUnicodeClass { char: escape_char },
+                    span,
+                });
+                return Token {
+                    kind: TokenKind::Error,
+                    span,
+                };
+            };
+
+            let name = str::from_utf8_unchecked(&self.bytes[name_start..name_end]);
+            Token {
+                kind: TokenKind::UnicodeClass {
+                    name: name.trim(),
+                    negated: escape_char == 'P',
+                },
+                span,
+            }
+        }
+    }
+
     unsafe fn consume_hex(&mut self, required_digits: usize) -> (u32, usize) {
         let mut value: u32 = 0;
         let mut consumed_count = 0;
@@ -317,6 +515,36 @@ impl<'src> Lexer<'src> {
         }
     }
 
+    fn is_identifier_ahead(&self) -> bool {
+        let rest = unsafe { str::from_utf8_unchecked(&self.bytes[self.offset..]) };
+        rest.trim_start_matches([' ', '\t'])
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+    }
+
+    fn is_continuation_allowed(&self) -> bool {
+        self.is_expecting_expr && !self.is_in_brackets && !self.is_in_braces && self.quote.is_none()
+    }
+
+    fn is_alternative_ahead(&self) -> bool {
+        let rest = unsafe { str::from_utf8_unchecked(&self.bytes[self.offset..]) };
+        rest.trim_start().starts_with('|')
+    }
+
+    fn is_line_break_ahead(&self) -> bool {
+        let rest = unsafe { str::from_utf8_unchecked(&self.bytes[self.offset + 1..]) };
+        rest.trim_start_matches([' ', '\t']).starts_with('\n')
+    }
+
+    fn is_reference_ahead(&self) -> bool {
+        let rest = unsafe { str::from_utf8_unchecked(&self.bytes[self.offset + 1..]) };
+        rest.trim_start_matches([' ', '\t'])
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+    }
+
     fn consume_number(&mut self) -> Option<usize> {
         let span_start = self.offset;
         while self.peek().is_some_and(|&c| c.is_ascii_digit()) {
@@ -354,6 +582,28 @@ impl<'src> Lexer<'src> {
         Some(value)
     }
 
+    fn consume_comment(&mut self) {
+        let span_start = self.offset;
+        self.consume();
+        if self.consume() == Some('/') {
+            while self.peek().is_some_and(|&c| c != '\n') {
+                self.consume();
+            }
+            return;
+        }
+
+        while let Some(char) = self.consume() {
+            if char == '*' && self.peek() == Some(&'/') {
+                self.consume();
+                return;
+            }
+        }
+        self.errors.push(Error {
+            kind: ErrorKind::UnterminatedComment,
+            span: Span::from((span_start, span_start + 2)),
+        });
+    }
+
     fn skip_whitespace(&mut self) {
         if self.is_in_brackets {
             return;
@@ -377,3 +627,66 @@ impl<'src> Lexer<'src> {
         }
     }
 }
+
+fn is_class_escape(char: char) -> bool {
+    is_shorthand_class(char) || matches!(char, 'p' | 'P')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::ErrorKind, span::Span, token::TokenKind};
+
+    use super::Lexer;
+
+    #[test]
+    fn unterminated_string_does_not_continue_onto_the_next_line() {
+        let source = "op = \"abc\n  | \"x\"\n";
+        let (tokens, _) = Lexer::new(source).lex();
+        let newline = tokens.iter().find(|token| matches!(token.kind, TokenKind::Newline));
+        let end_of_line = source.find('\n').unwrap();
+        assert_eq!(newline.map(|token| token.span.start), Some(end_of_line));
+    }
+
+    #[test]
+    fn escape_before_a_newline_ends_the_rule() {
+        for source in ["a = \"abc\\\nb = x\n", "a = [abc\\\nb = x\n"] {
+            let (tokens, errors) = Lexer::new(source).lex();
+            let backslash = source.find('\\').unwrap();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].kind, ErrorKind::DanglingEscape);
+            assert_eq!(errors[0].span, Span::from((backslash, backslash + 1)));
+
+            let next_rule = tokens.iter().position(|token| matches!(token.kind, TokenKind::Identifier { name: "b" }));
+            assert!(next_rule.is_some_and(|index| matches!(tokens[index - 1].kind, TokenKind::Newline)));
+        }
+    }
+
+    #[test]
+    fn braces_around_a_name_lex_as_a_reference() {
+        let source = "x = { digit }+\n";
+        let (tokens, errors) = Lexer::new(source).lex();
+        assert!(errors.is_empty());
+        let reference = tokens.iter().find(|token| matches!(token.kind, TokenKind::Reference { name: "digit" }));
+        let start = source.find('{').unwrap();
+        let end = source.find('}').unwrap() + 1;
+        assert_eq!(reference.map(|token| token.span), Some(Span::from((start, end))));
+    }
+
+    #[test]
+    fn reference_with_repetition_bounds_is_rejected() {
+        let source = "x = a{foo,3}\n";
+        let (_, errors) = Lexer::new(source).lex();
+        let start = source.find('{').unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ErrorKind::InvalidReference { name: String::from("foo") });
+        assert_eq!(errors[0].span, Span::from((start, start + "{foo,3}".len())));
+    }
+
+    #[test]
+    fn leading_pipe_continues_the_rule() {
+        let (tokens, errors) = Lexer::new("op = a\n  | b\n").lex();
+        assert!(errors.is_empty());
+        let newlines = tokens.iter().filter(|token| matches!(token.kind, TokenKind::Newline)).count();
+        assert_eq!(newlines, 1);
+    }
+}
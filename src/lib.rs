@@ -0,0 +1,26 @@
+pub mod ast;
+mod case_folding;
+pub mod codegen;
+mod compile;
+mod dfa;
+pub mod error;
+mod interpreter;
+mod lexer;
+mod linter;
+mod nfa;
+mod parser;
+mod regex;
+mod resolver;
+pub mod span;
+mod token;
+mod unicode;
+mod utf8;
+pub mod warning;
+
+pub use compile::{CompiledSpec, Diagnostics, ModeChange, compile};
+pub use error::Error;
+pub use interpreter::Interpreter;
+pub use lexer::Lexer;
+pub use parser::Parser;
+pub use span::Span;
+pub use warning::Warning;
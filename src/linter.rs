@@ -0,0 +1,212 @@
+use std::mem;
+
+use crate::{
+    ast::{ASTNode, Rule, RuleKind},
+    resolver::DependencyGraph,
+    warning::{Warning, WarningKind},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GroupContext {
+    Standalone,
+    Alternation,
+    Concat,
+    Quantifier,
+}
+
+#[derive(Debug)]
+pub struct Linter<'a, 'src> {
+    rules: &'a [Rule<'src>],
+    graph: &'a DependencyGraph,
+    warnings: Vec<Warning>,
+}
+
+impl<'a, 'src> Linter<'a, 'src> {
+    pub fn new(rules: &'a [Rule<'src>], graph: &'a DependencyGraph) -> Self {
+        Self {
+            rules,
+            graph,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn lint(&mut self) -> Vec<Warning> {
+        self.lint_identical_patterns();
+        for rule in self.rules {
+            self.lint_parentheses(&rule.pattern, GroupContext::Standalone);
+        }
+        self.lint_unused_helpers();
+        mem::take(&mut self.warnings)
+    }
+
+    fn lint_identical_patterns(&mut self) {
+        for (index, first) in self.rules.iter().enumerate() {
+            if matches!(first.pattern, ASTNode::Error) {
+                continue;
+            }
+            let Some(second) = self.rules[index + 1..]
+                .iter()
+                .find(|second| {
+                    is_same_mode(first, second)
+                        && first.is_case_insensitive == second.is_case_insensitive
+                        && is_same_pattern(&first.pattern, &second.pattern)
+                })
+            else {
+                continue;
+            };
+
+            self.warnings.push(Warning {
+                kind: WarningKind::IdenticalRulePatterns {
+                    name1: first.name.to_string(),
+                    span1: first.name_span.to_source_span(),
+                    name2: second.name.to_string(),
+                    span2: second.name_span.to_source_span(),
+                },
+                span: second.name_span.to_source_span(),
+            });
+        }
+    }
+
+    fn lint_parentheses(&mut self, node: &ASTNode<'src>, context: GroupContext) {
+        match node {
+            ASTNode::Group { inner, span } if matches!(**inner, ASTNode::Group { .. }) => {
+                self.warnings.push(Warning {
+                    kind: WarningKind::UnnecessaryParanthesis,
+                    span: span.to_source_span(),
+                });
+                self.lint_parentheses(inner, context);
+            }
+            ASTNode::Group { inner, span } => {
+                let is_unnecessary = match context {
+                    GroupContext::Standalone | GroupContext::Alternation => true,
+                    GroupContext::Concat => !matches!(**inner, ASTNode::Alternation { .. }),
+                    GroupContext::Quantifier => is_atom(inner),
+                };
+                if is_unnecessary {
+                    self.warnings.push(Warning {
+                        kind: WarningKind::UnnecessaryParanthesis,
+                        span: span.to_source_span(),
+                    });
+                }
+                self.lint_parentheses(inner, GroupContext::Standalone);
+            }
+            ASTNode::Alternation { left, right } => {
+                self.lint_parentheses(left, GroupContext::Alternation);
+                self.lint_parentheses(right, GroupContext::Alternation);
+            }
+            ASTNode::Concat { left, right } => {
+                self.lint_parentheses(left, GroupContext::Concat);
+                self.lint_parentheses(right, GroupContext::Concat);
+            }
+            ASTNode::CaseInsensitive { inner, .. } => self.lint_parentheses(inner, GroupContext::Standalone),
+            ASTNode::TrailingContext { head, tail, .. } => {
+                self.lint_parentheses(head, GroupContext::Standalone);
+                self.lint_parentheses(tail, GroupContext::Standalone);
+            }
+            ASTNode::Repeat0 { inner, .. }
+            | ASTNode::Repeat1 { inner }
+            | ASTNode::Optional { inner, .. }
+            | ASTNode::Range { inner, .. } => self.lint_parentheses(inner, GroupContext::Quantifier),
+            ASTNode::Literal { .. }
+            | ASTNode::Reference { .. }
+            | ASTNode::CharClass { .. }
+            | ASTNode::AnyChar
+            | ASTNode::LineStart { .. }
+            | ASTNode::LineEnd { .. }
+            | ASTNode::Error => {}
+        }
+    }
+
+    fn lint_unused_helpers(&mut self) {
+        let mut is_used = vec![false; self.rules.len()];
+        let mut stack: Vec<usize> = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| !matches!(rule.kind, RuleKind::Helper))
+            .map(|(rule_id, _)| rule_id)
+            .collect();
+
+        while let Some(rule_id) = stack.pop() {
+            for &(dependency, _) in &self.graph.dependencies[rule_id] {
+                if !is_used[dependency] {
+                    is_used[dependency] = true;
+                    stack.push(dependency);
+                }
+            }
+        }
+
+        for (rule, is_used) in self.rules.iter().zip(is_used) {
+            if matches!(rule.kind, RuleKind::Helper) && !is_used {
+                self.warnings.push(Warning {
+                    kind: WarningKind::UnusedHelperRule,
+                    span: rule.name_span.to_source_span(),
+                });
+            }
+        }
+    }
+}
+
+fn is_atom(node: &ASTNode) -> bool {
+    matches!(
+        node,
+        ASTNode::Literal { .. }
+            | ASTNode::Reference { .. }
+            | ASTNode::Group { .. }
+            | ASTNode::CharClass { .. }
+            | ASTNode::AnyChar
+    )
+}
+
+fn is_same_mode(first: &Rule, second: &Rule) -> bool {
+    matches!(first.kind, RuleKind::Helper) || matches!(second.kind, RuleKind::Helper) || first.mode == second.mode
+}
+
+fn is_same_pattern(first: &ASTNode, second: &ASTNode) -> bool {
+    match (first, second) {
+        (ASTNode::Group { inner, .. }, other) | (other, ASTNode::Group { inner, .. }) => is_same_pattern(inner, other),
+        (ASTNode::Literal { char: first }, ASTNode::Literal { char: second }) => first == second,
+        (ASTNode::Reference { name: first, .. }, ASTNode::Reference { name: second, .. }) => first == second,
+        (
+            ASTNode::Alternation { left: left1, right: right1 },
+            ASTNode::Alternation { left: left2, right: right2 },
+        )
+        | (ASTNode::Concat { left: left1, right: right1 }, ASTNode::Concat { left: left2, right: right2 })
+        | (
+            ASTNode::TrailingContext {
+                head: left1,
+                tail: right1,
+                ..
+            },
+            ASTNode::TrailingContext {
+                head: left2,
+                tail: right2,
+                ..
+            },
+        ) => is_same_pattern(left1, left2) && is_same_pattern(right1, right2),
+        (ASTNode::Repeat0 { inner: first, .. }, ASTNode::Repeat0 { inner: second, .. })
+        | (ASTNode::Repeat1 { inner: first }, ASTNode::Repeat1 { inner: second })
+        | (ASTNode::Optional { inner: first, .. }, ASTNode::Optional { inner: second, .. })
+        | (ASTNode::CaseInsensitive { inner: first, .. }, ASTNode::CaseInsensitive { inner: second, .. }) => {
+            is_same_pattern(first, second)
+        }
+        (
+            ASTNode::Range {
+                inner: first,
+                min: min1,
+                max: max1,
+                ..
+            },
+            ASTNode::Range {
+                inner: second,
+                min: min2,
+                max: max2,
+                ..
+            },
+        ) => min1 == min2 && max1 == max2 && is_same_pattern(first, second),
+        (ASTNode::CharClass { .. }, ASTNode::CharClass { .. }) | (ASTNode::AnyChar, ASTNode::AnyChar) => first == second,
+        (ASTNode::LineStart { .. }, ASTNode::LineStart { .. })
+        | (ASTNode::LineEnd { .. }, ASTNode::LineEnd { .. }) => true,
+        _ => false,
+    }
+}
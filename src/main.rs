@@ -1,27 +1,176 @@
-use std::fs;
-
-use miette::{Report, NamedSource};
-
-use crate::lexer::Lexer;
-
-mod error;
-mod lexer;
-mod ast;
-mod parser;
-mod regex;
-mod span;
-mod token;
-mod warning;
-
-fn main() {
-    let path = "./example/tokens.lexx";
-    let source = fs::read_to_string(path).expect("Failed to read file");
-    let named_source = NamedSource::new(path, source.clone());
-
-    let mut lexer = Lexer::new(&source);
-    let (tokens, errors) = lexer.lex();
-    for error in errors {
-        let report = Report::new(error).with_source_code(named_source.clone());
+use std::{env, fs, mem, path::Path, process::ExitCode};
+
+use lexx::{
+    CompiledSpec, Interpreter,
+    codegen::{RustGenerator, Strategy},
+};
+use miette::{Diagnostic, NamedSource, Report};
+
+use crate::cli::{Command, USAGE};
+
+mod cli;
+
+const UNMATCHED_NAME: &str = "<error>";
+
+fn main() -> ExitCode {
+    let command = match cli::parse_args(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match command {
+        Command::Help => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Command::Check { spec, deny_warnings } => check(&spec, deny_warnings),
+        Command::Build {
+            spec,
+            output,
+            strategy,
+            deny_warnings,
+        } => build(&spec, output.as_deref(), strategy, deny_warnings),
+        Command::Lex {
+            spec,
+            input,
+            deny_warnings,
+        } => lex(&spec, &input, deny_warnings),
+    }
+}
+
+fn check(path: &Path, deny_warnings: bool) -> ExitCode {
+    let Some((mut spec, named_source)) = load_spec(path) else {
+        return ExitCode::FAILURE;
+    };
+    let has_warnings = report_warnings(&mut spec, &named_source);
+    report_statistics(&spec);
+    exit_code(has_warnings && deny_warnings)
+}
+
+fn build(path: &Path, output: Option<&Path>, strategy: Strategy, deny_warnings: bool) -> ExitCode {
+    let Some((mut spec, named_source)) = load_spec(path) else {
+        return ExitCode::FAILURE;
+    };
+    let has_warnings = report_warnings(&mut spec, &named_source);
+    report_statistics(&spec);
+
+    let generated = match RustGenerator::new(&spec, strategy).generate() {
+        Ok(generated) => generated,
+        Err(errors) => {
+            report_all(errors, &named_source);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match output {
+        Some(output) => {
+            if let Err(error) = fs::write(output, generated) {
+                eprintln!("error: failed to write '{}': {error}", output.display());
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{generated}"),
+    }
+    exit_code(has_warnings && deny_warnings)
+}
+
+fn lex(path: &Path, input_path: &Path, deny_warnings: bool) -> ExitCode {
+    let Some((mut spec, named_source)) = load_spec(path) else {
+        return ExitCode::FAILURE;
+    };
+    let has_warnings = report_warnings(&mut spec, &named_source);
+
+    let input = match fs::read_to_string(input_path) {
+        Ok(input) => input,
+        Err(error) => {
+            eprintln!("error: failed to read '{}': {error}", input_path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let name_width = spec
+        .rules
+        .iter()
+        .map(|rule| rule.name.len())
+        .chain([UNMATCHED_NAME.len()])
+        .max()
+        .unwrap_or(0);
+    let interpreter = Interpreter::new(&spec, &input);
+    let mut unmatched_count = 0;
+    for lexeme in interpreter.clone() {
+        let name = match lexeme.rule {
+            Some(rule_id) => spec.rules[rule_id].name.as_str(),
+            None => {
+                unmatched_count += 1;
+                UNMATCHED_NAME
+            }
+        };
+        let span = format!("{}..{}", lexeme.span.start, lexeme.span.end);
+        println!("{name:<name_width$} {span:<12} {:?}", interpreter.slice(&lexeme));
+    }
+
+    if unmatched_count > 0 {
+        eprintln!("error: {unmatched_count} characters did not match any rule");
+        return ExitCode::FAILURE;
+    }
+    exit_code(has_warnings && deny_warnings)
+}
+
+fn load_spec(path: &Path) -> Option<(CompiledSpec, NamedSource<String>)> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("error: failed to read '{}': {error}", path.display());
+            return None;
+        }
+    };
+    let named_source = NamedSource::new(path.display().to_string(), source.clone());
+
+    match lexx::compile(&source) {
+        Ok(spec) => Some((spec, named_source)),
+        Err(diagnostics) => {
+            report_all(diagnostics.errors, &named_source);
+            report_all(diagnostics.warnings, &named_source);
+            None
+        }
+    }
+}
+
+fn report_warnings(spec: &mut CompiledSpec, named_source: &NamedSource<String>) -> bool {
+    let warnings = mem::take(&mut spec.warnings);
+    let has_warnings = !warnings.is_empty();
+    report_all(warnings, named_source);
+    has_warnings
+}
+
+fn report_all<D>(diagnostics: Vec<D>, named_source: &NamedSource<String>)
+where
+    D: Diagnostic + Send + Sync + 'static,
+{
+    for diagnostic in diagnostics {
+        let report = Report::new(diagnostic).with_source_code(named_source.clone());
         eprintln!("{:?}", report);
     }
 }
+
+fn report_statistics(spec: &CompiledSpec) {
+    let statistics = spec.statistics;
+    eprintln!(
+        "Compiled {} rules: {} NFA states, {} DFA states, {} after minimization",
+        spec.rules.len(),
+        statistics.nfa_states,
+        statistics.dfa_states,
+        statistics.minimized_dfa_states
+    );
+}
+
+fn exit_code(is_failure: bool) -> ExitCode {
+    if is_failure {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
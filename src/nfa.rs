@@ -0,0 +1,318 @@
+use std::mem;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast::{ASTNode, Rule, RuleKind},
+    regex::{complement_ranges, normalize_ranges, parts_to_ranges},
+    utf8::{ByteRange, utf8_sequences},
+};
+
+pub type StateId = usize;
+pub type RuleId = usize;
+pub type ModeId = usize;
+
+#[derive(Debug, Default)]
+pub struct State {
+    pub transitions: Vec<(ByteRange, StateId)>,
+    pub epsilons: Vec<StateId>,
+    pub accept: Option<RuleId>,
+    pub line_end_accept: Option<RuleId>,
+}
+
+#[derive(Debug)]
+pub struct Nfa {
+    pub states: Vec<State>,
+    pub starts: Vec<StateId>,
+    pub line_starts: Vec<StateId>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fragment {
+    start: StateId,
+    end: StateId,
+}
+
+#[derive(Debug)]
+pub struct NfaBuilder<'a, 'src> {
+    rules: &'a [Rule<'src>],
+    modes: &'a [&'src str],
+    rules_by_name: FxHashMap<&'src str, &'a Rule<'src>>,
+    states: Vec<State>,
+    references: Vec<&'src str>,
+}
+
+impl<'a, 'src> NfaBuilder<'a, 'src> {
+    pub fn new(rules: &'a [Rule<'src>], modes: &'a [&'src str]) -> Self {
+        let mut rules_by_name = FxHashMap::default();
+        for rule in rules {
+            rules_by_name.entry(rule.name).or_insert(rule);
+        }
+
+        Self {
+            rules,
+            modes,
+            rules_by_name,
+            states: Vec::new(),
+            references: Vec::new(),
+        }
+    }
+
+    pub fn build(&mut self) -> Nfa {
+        let starts: Vec<StateId> = self.modes.iter().map(|_| self.add_state()).collect();
+        let line_starts: Vec<StateId> = starts
+            .iter()
+            .map(|&start| {
+                let line_start = self.add_state();
+                self.add_epsilon(line_start, start);
+                line_start
+            })
+            .collect();
+
+        for (rule_id, rule) in self.rules.iter().enumerate() {
+            if matches!(rule.kind, RuleKind::Helper) {
+                continue;
+            }
+            let Some(mode_id) = self.modes.iter().position(|&mode| mode == rule.mode) else {
+                continue;
+            };
+            let start = if is_line_start_anchored(&rule.pattern) {
+                line_starts[mode_id]
+            } else {
+                starts[mode_id]
+            };
+
+            let fragment = self.build_node(&rule.pattern);
+            let accept = self.add_state();
+            if is_line_end_anchored(&rule.pattern) {
+                self.states[accept].line_end_accept = Some(rule_id);
+            } else {
+                self.states[accept].accept = Some(rule_id);
+            }
+            self.add_epsilon(start, fragment.start);
+            self.add_epsilon(fragment.end, accept);
+        }
+
+        Nfa {
+            states: mem::take(&mut self.states),
+            starts,
+            line_starts,
+        }
+    }
+
+    pub fn build_pattern(&mut self, rule_id: RuleId, pattern: &ASTNode<'src>) -> Nfa {
+        let start = self.add_state();
+        let line_start = self.add_state();
+        let fragment = self.build_node(pattern);
+        let accept = self.add_state();
+        self.states[accept].accept = Some(rule_id);
+        self.add_epsilon(line_start, fragment.start);
+        if !is_line_start_anchored(pattern) {
+            self.add_epsilon(start, fragment.start);
+        }
+        self.add_epsilon(fragment.end, accept);
+
+        Nfa {
+            states: mem::take(&mut self.states),
+            starts: vec![start],
+            line_starts: vec![line_start],
+        }
+    }
+
+    fn build_node(&mut self, node: &ASTNode<'src>) -> Fragment {
+        match node {
+            ASTNode::Literal { char } => self.build_ranges(&[(*char, *char)]),
+
+            ASTNode::Reference { name, span: _ } => {
+                let Some(rule) = self.rules_by_name.get(name).copied() else {
+                    return self.build_empty_language();
+                };
+                if self.references.contains(name) {
+                    return self.build_empty_language();
+                }
+
+                self.references.push(name);
+                let fragment = self.build_node(&rule.pattern);
+                self.references.pop();
+                fragment
+            }
+
+            ASTNode::Alternation { left, right } => {
+                let left = self.build_node(left);
+                let right = self.build_node(right);
+                let start = self.add_state();
+                let end = self.add_state();
+                self.add_epsilon(start, left.start);
+                self.add_epsilon(start, right.start);
+                self.add_epsilon(left.end, end);
+                self.add_epsilon(right.end, end);
+                Fragment { start, end }
+            }
+
+            ASTNode::Concat { left, right }
+            | ASTNode::TrailingContext {
+                head: left,
+                tail: right,
+                ..
+            } => {
+                let left = self.build_node(left);
+                let right = self.build_node(right);
+                self.add_epsilon(left.end, right.start);
+                Fragment {
+                    start: left.start,
+                    end: right.end,
+                }
+            }
+
+            ASTNode::Repeat0 { inner, .. } => {
+                let inner = self.build_node(inner);
+                let start = self.add_state();
+                let end = self.add_state();
+                self.add_epsilon(start, inner.start);
+                self.add_epsilon(start, end);
+                self.add_epsilon(inner.end, inner.start);
+                self.add_epsilon(inner.end, end);
+                Fragment { start, end }
+            }
+
+            ASTNode::Repeat1 { inner } => {
+                let inner = self.build_node(inner);
+                let end = self.add_state();
+                self.add_epsilon(inner.end, inner.start);
+                self.add_epsilon(inner.end, end);
+                Fragment {
+                    start: inner.start,
+                    end,
+                }
+            }
+
+            ASTNode::Optional { inner, .. } => {
+                let inner = self.build_node(inner);
+                let start = self.add_state();
+                let end = self.add_state();
+                self.add_epsilon(start, inner.start);
+                self.add_epsilon(start, end);
+                self.add_epsilon(inner.end, end);
+                Fragment { start, end }
+            }
+
+            ASTNode::Group { inner, span: _ } | ASTNode::CaseInsensitive { inner, span: _ } => self.build_node(inner),
+
+            ASTNode::Range { inner, min, max, .. } => self.build_range(inner, *min, *max),
+
+            ASTNode::CharClass { negated, parts } => {
+                let ranges = parts_to_ranges(parts);
+                let ranges = if *negated {
+                    complement_ranges(&ranges)
+                } else {
+                    normalize_ranges(&ranges)
+                };
+                self.build_ranges(&ranges)
+            }
+
+            ASTNode::LineStart { .. } | ASTNode::LineEnd { .. } => {
+                let state = self.add_state();
+                Fragment {
+                    start: state,
+                    end: state,
+                }
+            }
+
+            ASTNode::AnyChar => self.build_ranges(&complement_ranges(&[('\n', '\n')])),
+
+            ASTNode::Error => self.build_empty_language(),
+        }
+    }
+
+    fn build_range(&mut self, inner: &ASTNode<'src>, min: usize, max: Option<usize>) -> Fragment {
+        let start = self.add_state();
+        let mut current = start;
+        for _ in 0..min {
+            let fragment = self.build_node(inner);
+            self.add_epsilon(current, fragment.start);
+            current = fragment.end;
+        }
+
+        let end = self.add_state();
+        match max {
+            Some(max) => {
+                for _ in min..max {
+                    let fragment = self.build_node(inner);
+                    self.add_epsilon(current, end);
+                    self.add_epsilon(current, fragment.start);
+                    current = fragment.end;
+                }
+            }
+            None => {
+                let fragment = self.build_node(inner);
+                self.add_epsilon(current, fragment.start);
+                self.add_epsilon(fragment.end, fragment.start);
+                self.add_epsilon(fragment.end, end);
+            }
+        }
+        self.add_epsilon(current, end);
+        Fragment { start, end }
+    }
+
+    fn build_ranges(&mut self, ranges: &[(char, char)]) -> Fragment {
+        let start = self.add_state();
+        let end = self.add_state();
+        for &(min, max) in ranges {
+            for sequence in utf8_sequences(min, max) {
+                let mut current = start;
+                for (i, &byte_range) in sequence.iter().enumerate() {
+                    if i + 1 == sequence.len() {
+                        self.states[current].transitions.push((byte_range, end));
+                        break;
+                    }
+
+                    let shared = self.states[current]
+                        .transitions
+                        .iter()
+                        .find(|&&(range, to)| range == byte_range && to != end)
+                        .map(|&(_, to)| to);
+                    current = match shared {
+                        Some(next) => next,
+                        None => {
+                            let next = self.add_state();
+                            self.states[current].transitions.push((byte_range, next));
+                            next
+                        }
+                    };
+                }
+            }
+        }
+        Fragment { start, end }
+    }
+
+    fn build_empty_language(&mut self) -> Fragment {
+        let start = self.add_state();
+        let end = self.add_state();
+        Fragment { start, end }
+    }
+
+    fn add_state(&mut self) -> StateId {
+        self.states.push(State::default());
+        self.states.len() - 1
+    }
+
+    fn add_epsilon(&mut self, from: StateId, to: StateId) {
+        self.states[from].epsilons.push(to);
+    }
+}
+
+fn is_line_start_anchored(node: &ASTNode) -> bool {
+    match node {
+        ASTNode::LineStart { .. } => true,
+        ASTNode::Concat { left, .. } | ASTNode::TrailingContext { head: left, .. } => is_line_start_anchored(left),
+        _ => false,
+    }
+}
+
+fn is_line_end_anchored(node: &ASTNode) -> bool {
+    match node {
+        ASTNode::LineEnd { .. } => true,
+        ASTNode::Concat { right, .. } => is_line_end_anchored(right),
+        _ => false,
+    }
+}
@@ -1,33 +1,322 @@
-use crate::{ast::ASTNode, error::Error, token::{Token, TokenKind}, warning::Warning};
+use std::mem;
+
+use crate::{
+    ast::{ASTNode, DEFAULT_MODE, Rule, RuleAction, RuleKind},
+    error::{Error, ErrorKind},
+    span::Span,
+    token::{Token, TokenKind},
+    warning::Warning,
+};
 
 mod regex;
 
 #[derive(Debug)]
 pub struct Parser<'src> {
-    tokens: &'src [Token<'src>],
+    tokens: Vec<Token<'src>>,
     index: usize,
+    mode: &'src str,
     errors: Vec<Error>,
     warnings: Vec<Warning>,
 }
 
 impl<'src> Iterator for Parser<'src> {
-    type Item = ASTNode<'src>;
+    type Item = Rule<'src>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        todo!()
+        loop {
+            while self.peek().is_some_and(|token| matches!(token.kind, TokenKind::Newline)) {
+                self.consume();
+            }
+            self.peek()?;
+
+            let rule_start = self.index;
+            if self.peek().is_some_and(|token| matches!(token.kind, TokenKind::Mode)) {
+                if let Err(error) = self.parse_mode() {
+                    self.errors.push(error);
+                    self.synchronize(rule_start);
+                }
+                continue;
+            }
+
+            let kind = self.parse_rule_kind();
+            let is_case_insensitive = self.peek().is_some_and(|token| matches!(token.kind, TokenKind::NoCase));
+            if is_case_insensitive {
+                self.consume();
+            }
+            let (name, name_span, action) = match self.parse_rule_header() {
+                Ok(header) => header,
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize(rule_start);
+                    continue;
+                }
+            };
+
+            let pattern = match self.parse_pattern(&kind) {
+                Ok(pattern) => pattern,
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize(rule_start);
+                    ASTNode::Error
+                }
+            };
+
+            return Some(Rule {
+                name,
+                name_span,
+                kind,
+                is_case_insensitive,
+                mode: self.mode,
+                action,
+                pattern,
+            });
+        }
     }
 }
 
 impl<'src> Parser<'src> {
     pub fn new(tokens: &'src [Token]) -> Self {
         Self {
-            tokens,
+            tokens: tokens
+                .iter()
+                .filter(|token| !matches!(token.kind, TokenKind::Comment))
+                .copied()
+                .collect(),
             index: 0,
+            mode: DEFAULT_MODE,
             errors: Vec::new(),
             warnings: Vec::new(),
         }
     }
 
+    pub fn parse(&mut self) -> (Vec<Rule<'src>>, Vec<Error>, Vec<Warning>) {
+        let mut rules: Vec<Rule> = Vec::new();
+        for rule in self.by_ref() {
+            rules.push(rule);
+        }
+
+        let errors = mem::take(&mut self.errors);
+        let warnings = mem::take(&mut self.warnings);
+        (rules, errors, warnings)
+    }
+
+    fn parse_rule_kind(&mut self) -> RuleKind {
+        let kind = match self.peek().map(|token| token.kind) {
+            Some(TokenKind::Helper) => RuleKind::Helper,
+            Some(TokenKind::Skip) => RuleKind::Skip,
+            _ => return RuleKind::Token,
+        };
+        self.consume();
+        kind
+    }
+
+    fn parse_mode(&mut self) -> Result<(), Error> {
+        self.consume();
+        let token = self.expect_token()?;
+        let TokenKind::Identifier { name } = token.kind else {
+            return Err(Error {
+                kind: ErrorKind::ExpectedModeName,
+                span: token.span,
+            });
+        };
+        self.mode = name;
+
+        if let Some(token) = self.consume()
+            && !matches!(token.kind, TokenKind::Newline)
+        {
+            return Err(Error {
+                kind: ErrorKind::UnexpectedToken,
+                span: token.span,
+            });
+        }
+        Ok(())
+    }
+
+    fn parse_rule_header(&mut self) -> Result<(&'src str, Span, Option<RuleAction<'src>>), Error> {
+        let token = self.expect_token()?;
+        let TokenKind::Identifier { name } = token.kind else {
+            return Err(Error {
+                kind: ErrorKind::ExpectedRuleName,
+                span: token.span,
+            });
+        };
+
+        let action = match self.peek() {
+            Some(arrow) if matches!(arrow.kind, TokenKind::Arrow) => {
+                self.consume();
+                Some(self.parse_rule_action()?)
+            }
+            _ => None,
+        };
+
+        let Some(equals) = self.peek() else {
+            return Err(Error {
+                kind: ErrorKind::ExpectedEquals,
+                span: self.eof_span(),
+            });
+        };
+        if !matches!(equals.kind, TokenKind::Equals) {
+            return Err(Error {
+                kind: ErrorKind::ExpectedEquals,
+                span: equals.span,
+            });
+        }
+        self.consume();
+
+        Ok((name, token.span, action))
+    }
+
+    fn parse_rule_action(&mut self) -> Result<RuleAction<'src>, Error> {
+        let token = self.expect_token()?;
+        let TokenKind::Identifier { name } = token.kind else {
+            return Err(Error {
+                kind: ErrorKind::ExpectedRuleAction,
+                span: token.span,
+            });
+        };
+
+        match name {
+            "pop" => Ok(RuleAction::Pop { span: token.span }),
+            "push" | "enter" => {
+                let open = self.expect_token()?;
+                if !matches!(open.kind, TokenKind::OpenParen) {
+                    return Err(Error {
+                        kind: ErrorKind::ExpectedModeName,
+                        span: open.span,
+                    });
+                }
+                let mode_token = self.expect_token()?;
+                let TokenKind::Identifier { name: mode } = mode_token.kind else {
+                    return Err(Error {
+                        kind: ErrorKind::ExpectedModeName,
+                        span: mode_token.span,
+                    });
+                };
+                let Some(close) = self
+                    .peek()
+                    .filter(|token| matches!(token.kind, TokenKind::ClosedParen))
+                else {
+                    return Err(Error {
+                        kind: ErrorKind::ExpectedClosedParen,
+                        span: open.span,
+                    });
+                };
+                self.consume();
+
+                let span = Span::from((token.span.start, close.span.end));
+                if name == "push" {
+                    Ok(RuleAction::Push { mode, span })
+                } else {
+                    Ok(RuleAction::Enter { mode, span })
+                }
+            }
+            _ => Err(Error {
+                kind: ErrorKind::ExpectedRuleAction,
+                span: token.span,
+            }),
+        }
+    }
+
+    fn parse_pattern(&mut self, kind: &RuleKind) -> Result<ASTNode<'src>, Error> {
+        let line_start = self.peek().filter(|token| matches!(token.kind, TokenKind::Caret));
+        if let Some(caret) = line_start {
+            self.consume();
+            if matches!(kind, RuleKind::Helper) {
+                return Err(Error {
+                    kind: ErrorKind::MisplacedAnchor,
+                    span: caret.span,
+                });
+            }
+        }
+
+        let mut pattern = self.parse_alternation()?;
+        if let Some(caret) = line_start {
+            pattern = ASTNode::Concat {
+                left: Box::new(ASTNode::LineStart { span: caret.span }),
+                right: Box::new(pattern),
+            };
+        }
+
+        while let Some(slash) = self.peek()
+            && matches!(slash.kind, TokenKind::Slash)
+        {
+            let is_nested = matches!(pattern, ASTNode::TrailingContext { .. });
+            if is_nested || matches!(kind, RuleKind::Helper) {
+                return Err(Error {
+                    kind: ErrorKind::MisplacedTrailingContext,
+                    span: slash.span,
+                });
+            }
+            self.consume();
+            let tail = self.parse_alternation()?;
+            pattern = ASTNode::TrailingContext {
+                head: Box::new(pattern),
+                tail: Box::new(tail),
+                span: slash.span,
+            };
+        }
+
+        if let Some(dollar) = self.peek()
+            && matches!(dollar.kind, TokenKind::Dollar)
+        {
+            self.consume();
+            let is_rule_end = self.peek().is_none_or(|token| matches!(token.kind, TokenKind::Newline));
+            let is_trailing_context = matches!(pattern, ASTNode::TrailingContext { .. });
+            if !is_rule_end || is_trailing_context || matches!(kind, RuleKind::Helper) {
+                return Err(Error {
+                    kind: ErrorKind::MisplacedAnchor,
+                    span: dollar.span,
+                });
+            }
+            pattern = ASTNode::Concat {
+                left: Box::new(pattern),
+                right: Box::new(ASTNode::LineEnd { span: dollar.span }),
+            };
+        }
+
+        if let Some(token) = self.consume()
+            && !matches!(token.kind, TokenKind::Newline)
+        {
+            return Err(Error {
+                kind: ErrorKind::UnexpectedToken,
+                span: token.span,
+            });
+        }
+        Ok(pattern)
+    }
+
+    fn synchronize(&mut self, rule_start: usize) {
+        let is_rule_ended = self.index > rule_start
+            && matches!(self.tokens[self.index - 1].kind, TokenKind::Newline);
+        if is_rule_ended {
+            return;
+        }
+        while let Some(token) = self.consume() {
+            if matches!(token.kind, TokenKind::Newline) {
+                break;
+            }
+        }
+    }
+
+    fn expect_token(&mut self) -> Result<Token<'src>, Error> {
+        self.consume().ok_or_else(|| Error {
+            kind: ErrorKind::UnexpectedEOF,
+            span: self.eof_span(),
+        })
+    }
+
+    fn eof_span(&self) -> Span {
+        let end = self.tokens.last().map_or(0, |token| token.span.end);
+        Span::from((end, end))
+    }
+
+    fn rule_end_span(&self) -> Span {
+        match self.peek() {
+            Some(token) => Span::from((token.span.start, token.span.start)),
+            None => self.eof_span(),
+        }
+    }
+
     fn peek(&self) -> Option<Token<'src>> {
         if self.index < self.tokens.len() {
             Some(self.tokens[self.index])
@@ -46,3 +335,67 @@ impl<'src> Parser<'src> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use miette::Diagnostic;
+
+    use crate::{error::ErrorKind, lexer::Lexer, span::Span};
+
+    use super::Parser;
+
+    fn parse(source: &str) -> (Vec<String>, Vec<(ErrorKind, Span)>) {
+        let (tokens, _) = Lexer::new(source).lex();
+        let (rules, errors, _) = Parser::new(&tokens).parse();
+        let names = rules.iter().map(|rule| rule.name.to_string()).collect();
+        (names, errors.into_iter().map(|error| (error.kind, error.span)).collect())
+    }
+
+    fn span_of(source: &str, needle: &str) -> Span {
+        let start = source.find(needle).unwrap();
+        Span::from((start, start + needle.len()))
+    }
+
+    #[test]
+    fn malformed_rules_resynchronize_at_the_next_line() {
+        let source = "a = x)\n? = y\nb = *\nc = z\n";
+        let (names, errors) = parse(source);
+        assert_eq!(names, ["a", "b", "c"]);
+        assert_eq!(
+            errors,
+            [
+                (ErrorKind::UnexpectedToken, span_of(source, ")")),
+                (ErrorKind::ExpectedRuleName, span_of(source, "?")),
+                (ErrorKind::ExpectedExpression, span_of(source, "*")),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_delimiters_label_the_opening_and_the_rule_end() {
+        for source in ["a = (xy\n", "a = [xy\n", "a = x{2,3\n", "a = (x"] {
+            let open = source.find(['(', '[', '{']).unwrap();
+            let open_span = Span::from((open, open + 1));
+            let rule_end = source.trim_end_matches('\n').len();
+            let rule_end = Span::from((rule_end, rule_end));
+            let kind = match &source[open..open + 1] {
+                "(" => ErrorKind::UnclosedParen { open_span },
+                "[" => ErrorKind::UnclosedBracket { open_span },
+                _ => ErrorKind::UnclosedBrace { open_span },
+            };
+
+            let (tokens, _) = Lexer::new(source).lex();
+            let (_, errors, _) = Parser::new(&tokens).parse();
+            let labels: Vec<(String, Span)> = errors[0]
+                .labels()
+                .unwrap()
+                .map(|label| (label.label().unwrap().to_string(), Span::from((label.offset(), label.offset() + label.len()))))
+                .collect();
+            assert_eq!(
+                labels,
+                [(String::from("opened here"), open_span), (String::from("rule ends here"), rule_end)]
+            );
+            assert_eq!(parse(source).1, [(kind, rule_end)]);
+        }
+    }
+}
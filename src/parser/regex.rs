@@ -0,0 +1,417 @@
+use crate::{
+    ast::{ASTNode, CharClassPart, MAX_REPETITION},
+    error::{Error, ErrorKind},
+    regex::{complement_ranges, is_negated_shorthand_class, ranges_to_parts, shorthand_class_ranges},
+    span::Span,
+    token::{Token, TokenKind},
+    unicode,
+    warning::{Warning, WarningKind},
+};
+
+use super::Parser;
+
+
+impl<'src> Parser<'src> {
+    pub(super) fn parse_alternation(&mut self) -> Result<ASTNode<'src>, Error> {
+        let mut left = self.parse_concat()?;
+        while self.peek().is_some_and(|token| matches!(token.kind, TokenKind::Pipe)) {
+            self.consume();
+            let right = self.parse_concat()?;
+            left = ASTNode::Alternation {
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_concat(&mut self) -> Result<ASTNode<'src>, Error> {
+        let mut node: Option<ASTNode> = None;
+        while let Some(token) = self.peek() {
+            if matches!(
+                token.kind,
+                TokenKind::Pipe
+                    | TokenKind::ClosedParen
+                    | TokenKind::Slash
+                    | TokenKind::Dollar
+                    | TokenKind::Newline
+            ) {
+                break;
+            }
+
+            let right = self.parse_repeat()?;
+            node = Some(match node {
+                Some(left) => ASTNode::Concat {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+                None => right,
+            });
+        }
+
+        node.ok_or_else(|| Error {
+            kind: ErrorKind::ExpectedExpression,
+            span: self.peek().map_or_else(|| self.eof_span(), |token| token.span),
+        })
+    }
+
+    fn parse_repeat(&mut self) -> Result<ASTNode<'src>, Error> {
+        let start = self.peek().map_or_else(|| self.eof_span(), |token| token.span).start;
+        let mut node = self.parse_atom()?;
+        while let Some(token) = self.peek() {
+            let span = Span::from((start, token.span.end));
+            node = match token.kind {
+                TokenKind::Star => {
+                    self.consume();
+                    ASTNode::Repeat0 {
+                        inner: Box::new(node),
+                        span,
+                    }
+                }
+                TokenKind::Plus => {
+                    self.consume();
+                    ASTNode::Repeat1 { inner: Box::new(node) }
+                }
+                TokenKind::QMark => {
+                    self.consume();
+                    ASTNode::Optional {
+                        inner: Box::new(node),
+                        span,
+                    }
+                }
+                TokenKind::OpenBrace => {
+                    let (min, max, close) = self.parse_repetition()?;
+                    ASTNode::Range {
+                        inner: Box::new(node),
+                        min,
+                        max,
+                        span: Span::from((start, close.end)),
+                    }
+                }
+                _ => break,
+            };
+        }
+        Ok(node)
+    }
+
+    fn parse_atom(&mut self) -> Result<ASTNode<'src>, Error> {
+        let token = self.expect_token()?;
+        match token.kind {
+            TokenKind::Literal { char } => Ok(ASTNode::Literal { char }),
+            TokenKind::Period => Ok(ASTNode::AnyChar),
+            TokenKind::ShorthandClass { char } => Ok(ASTNode::CharClass {
+                negated: is_negated_shorthand_class(char),
+                parts: ranges_to_parts(shorthand_class_ranges(char)),
+            }),
+            TokenKind::UnicodeClass { name, negated } => Ok(ASTNode::CharClass {
+                negated,
+                parts: ranges_to_parts(&unicode_class_ranges(name, token.span)?),
+            }),
+            TokenKind::OpenParen => self.parse_group(token),
+            TokenKind::OpenBrack => self.parse_char_class(token),
+            TokenKind::Reference { name } => Ok(ASTNode::Reference { name, span: token.span }),
+            TokenKind::Quote => self.parse_string(token),
+            TokenKind::Error => Ok(ASTNode::Error),
+            TokenKind::Caret => Err(Error {
+                kind: ErrorKind::MisplacedAnchor,
+                span: token.span,
+            }),
+            TokenKind::Star | TokenKind::Plus | TokenKind::QMark | TokenKind::OpenBrace => Err(Error {
+                kind: ErrorKind::ExpectedExpression,
+                span: token.span,
+            }),
+            _ => Err(Error {
+                kind: ErrorKind::UnexpectedToken,
+                span: token.span,
+            }),
+        }
+    }
+
+    fn parse_group(&mut self, open: Token<'src>) -> Result<ASTNode<'src>, Error> {
+        let is_case_insensitive = self.peek().is_some_and(|token| matches!(token.kind, TokenKind::QMark));
+        if is_case_insensitive {
+            self.parse_group_flags(open)?;
+        }
+
+        let inner = self.parse_alternation()?;
+        if let Some(token) = self.peek() {
+            let kind = match token.kind {
+                TokenKind::Slash => Some(ErrorKind::MisplacedTrailingContext),
+                TokenKind::Dollar => Some(ErrorKind::MisplacedAnchor),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                return Err(Error { kind, span: token.span });
+            }
+        }
+        let Some(close) = self
+            .peek()
+            .filter(|token| matches!(token.kind, TokenKind::ClosedParen))
+        else {
+            return Err(Error {
+                kind: ErrorKind::UnclosedParen { open_span: open.span },
+                span: self.rule_end_span(),
+            });
+        };
+        self.consume();
+
+        let span = Span::from((open.span.start, close.span.end));
+        if is_case_insensitive {
+            return Ok(ASTNode::CaseInsensitive {
+                inner: Box::new(inner),
+                span,
+            });
+        }
+        Ok(ASTNode::Group {
+            inner: Box::new(inner),
+            span,
+        })
+    }
+
+    fn parse_group_flags(&mut self, open: Token<'src>) -> Result<(), Error> {
+        self.consume();
+        let flag = self.expect_token()?;
+        let colon = self.expect_token()?;
+        if !matches!(flag.kind, TokenKind::Literal { char: 'i' }) || !matches!(colon.kind, TokenKind::Literal { char: ':' }) {
+            return Err(Error {
+                kind: ErrorKind::InvalidGroupFlags,
+                span: Span::from((open.span.start, colon.span.end)),
+            });
+        }
+        Ok(())
+    }
+
+    fn parse_string(&mut self, open: Token<'src>) -> Result<ASTNode<'src>, Error> {
+        let mut node: Option<ASTNode> = None;
+        let mut is_error = false;
+        loop {
+            let Some(token) = self
+                .peek()
+                .filter(|token| !matches!(token.kind, TokenKind::Newline))
+            else {
+                return Err(Error {
+                    kind: ErrorKind::UnterminatedString,
+                    span: open.span,
+                });
+            };
+            self.consume();
+
+            let right = match token.kind {
+                TokenKind::Quote => break,
+                TokenKind::Error => {
+                    is_error = true;
+                    continue;
+                }
+                TokenKind::Literal { char } => ASTNode::Literal { char },
+                _ => {
+                    return Err(Error {
+                        kind: ErrorKind::UnexpectedToken,
+                        span: token.span,
+                    });
+                }
+            };
+            node = Some(match node {
+                Some(left) => ASTNode::Concat {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+                None => right,
+            });
+        }
+
+        if is_error {
+            return Ok(ASTNode::Error);
+        }
+        node.ok_or_else(|| Error {
+            kind: ErrorKind::EmptyString,
+            span: Span::from((open.span.start, self.tokens[self.index - 1].span.end)),
+        })
+    }
+
+    fn parse_repetition(&mut self) -> Result<(usize, Option<usize>, Span), Error> {
+        let Some(open) = self.consume() else {
+            unreachable!("parse_repetition is only called on an open brace");
+        };
+        let min = self.expect_number()?;
+
+        let is_comma = self.peek().is_some_and(|token| matches!(token.kind, TokenKind::Comma));
+        let max = if !is_comma {
+            Some(min)
+        } else {
+            self.consume();
+            match self.peek().map(|token| token.kind) {
+                Some(TokenKind::ClosedBrace | TokenKind::Newline) | None => None,
+                _ => Some(self.expect_number()?),
+            }
+        };
+
+        let close = self.expect_closed_brace(open)?;
+        let span = Span::from((open.span.start, close.span.end));
+        if let Some(max) = max
+            && min > max
+        {
+            return Err(Error {
+                kind: ErrorKind::InvalidRepetitionRange { min, max },
+                span,
+            });
+        }
+        let bound = max.unwrap_or(min);
+        if bound > MAX_REPETITION {
+            return Err(Error {
+                kind: ErrorKind::RepetitionTooLarge {
+                    value: bound,
+                    limit: MAX_REPETITION,
+                },
+                span,
+            });
+        }
+        if is_comma && max == Some(min) {
+            self.warnings.push(Warning {
+                kind: WarningKind::UnnecessaryRangeBoundary { value: min },
+                span: span.to_source_span(),
+            });
+        }
+        Ok((min, max, close.span))
+    }
+
+    fn expect_number(&mut self) -> Result<usize, Error> {
+        let token = self.expect_token()?;
+        match token.kind {
+            TokenKind::Number { num } => Ok(num),
+            _ => Err(Error {
+                kind: ErrorKind::ExpectedNumber,
+                span: token.span,
+            }),
+        }
+    }
+
+    fn expect_closed_brace(&mut self, open: Token<'src>) -> Result<Token<'src>, Error> {
+        match self.peek() {
+            Some(token) if matches!(token.kind, TokenKind::ClosedBrace) => {
+                self.consume();
+                Ok(token)
+            }
+            Some(token) if !matches!(token.kind, TokenKind::Newline) => Err(Error {
+                kind: ErrorKind::UnexpectedToken,
+                span: token.span,
+            }),
+            _ => Err(Error {
+                kind: ErrorKind::UnclosedBrace { open_span: open.span },
+                span: self.rule_end_span(),
+            }),
+        }
+    }
+
+    fn parse_char_class(&mut self, open: Token<'src>) -> Result<ASTNode<'src>, Error> {
+        let negated = self.peek().is_some_and(|token| matches!(token.kind, TokenKind::Caret));
+        if negated {
+            self.consume();
+        }
+
+        let mut parts: Vec<CharClassPart> = Vec::new();
+        let mut is_error = false;
+        loop {
+            let Some(token) = self
+                .peek()
+                .filter(|token| !matches!(token.kind, TokenKind::Newline))
+            else {
+                return Err(Error {
+                    kind: ErrorKind::UnclosedBracket { open_span: open.span },
+                    span: self.rule_end_span(),
+                });
+            };
+            self.consume();
+
+            match token.kind {
+                TokenKind::ClosedBrack => break,
+                TokenKind::Error => is_error = true,
+                TokenKind::ShorthandClass { char } => {
+                    let ranges = shorthand_class_ranges(char);
+                    if is_negated_shorthand_class(char) {
+                        parts.extend(ranges_to_parts(&complement_ranges(ranges)));
+                    } else {
+                        parts.extend(ranges_to_parts(ranges));
+                    }
+                }
+                TokenKind::UnicodeClass { name, negated } => {
+                    let ranges = unicode_class_ranges(name, token.span)?;
+                    if negated {
+                        parts.extend(ranges_to_parts(&complement_ranges(&ranges)));
+                    } else {
+                        parts.extend(ranges_to_parts(&ranges));
+                    }
+                }
+                _ => {
+                    let min = class_char(token)?;
+                    let is_range = self.peek().is_some_and(|token| matches!(token.kind, TokenKind::Hyphen))
+                        && self
+                            .tokens
+                            .get(self.index + 1)
+                            .is_some_and(|token| !matches!(token.kind, TokenKind::ClosedBrack | TokenKind::Newline));
+                    if !is_range {
+                        parts.push(CharClassPart::Literal { char: min });
+                        continue;
+                    }
+
+                    self.consume();
+                    let end = self.expect_token()?;
+                    if matches!(end.kind, TokenKind::Error) {
+                        is_error = true;
+                        continue;
+                    }
+                    let max = class_char(end)?;
+                    if min > max {
+                        return Err(Error {
+                            kind: ErrorKind::InvalidCharRange { min, max },
+                            span: Span::from((token.span.start, end.span.end)),
+                        });
+                    }
+                    parts.push(CharClassPart::Range { min, max });
+                }
+            }
+        }
+
+        if is_error {
+            return Ok(ASTNode::Error);
+        }
+        if parts.is_empty() {
+            return Err(Error {
+                kind: ErrorKind::EmptyCharClass,
+                span: Span::from((open.span.start, self.tokens[self.index - 1].span.end)),
+            });
+        }
+        Ok(ASTNode::CharClass { negated, parts })
+    }
+}
+
+fn class_char(token: Token) -> Result<char, Error> {
+    let char = match token.kind {
+        TokenKind::Literal { char } => char,
+        TokenKind::Pipe => '|',
+        TokenKind::Star => '*',
+        TokenKind::Plus => '+',
+        TokenKind::QMark => '?',
+        TokenKind::OpenParen => '(',
+        TokenKind::ClosedParen => ')',
+        TokenKind::OpenBrack => '[',
+        TokenKind::Hyphen => '-',
+        TokenKind::Period => '.',
+        TokenKind::Caret => '^',
+        TokenKind::Dollar => '$',
+        TokenKind::Slash => '/',
+        _ => {
+            return Err(Error {
+                kind: ErrorKind::UnexpectedToken,
+                span: token.span,
+            });
+        }
+    };
+    Ok(char)
+}
+
+fn unicode_class_ranges(name: &str, span: Span) -> Result<Vec<(char, char)>, Error> {
+    unicode::property_ranges(name).ok_or_else(|| Error {
+        kind: ErrorKind::UnknownUnicodeProperty { name: name.to_string() },
+        span,
+    })
+}
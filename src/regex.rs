@@ -1,3 +1,5 @@
+use crate::ast::CharClassPart;
+
 pub fn is_shorthand_class(char: char) -> bool {
     matches!(char, 'd' | 'D' | 'w' | 'W' | 's' | 'S')
 }
@@ -19,6 +21,98 @@ pub fn control_to_literal(char: char) -> Option<char> {
 pub fn is_escaped_literal(char: char) -> bool {
     matches!(
         char,
-        '\\' | '|' | '*' | '+' | '?' | '{' | '}' | '[' | ']' | '(' | ')' | '-' | '.' | '^' | '$'
+        '\\' | '|' | '*' | '+' | '?' | '{' | '}' | '[' | ']' | '(' | ')' | '-' | '.' | '^' | '$' | '/' | '"' | '\''
     )
 }
+
+pub fn shorthand_class_ranges(char: char) -> &'static [(char, char)] {
+    match char.to_ascii_lowercase() {
+        'd' => &[('0', '9')],
+        'w' => &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')],
+        's' => &[('\t', '\r'), (' ', ' ')],
+        _ => &[],
+    }
+}
+
+pub fn is_negated_shorthand_class(char: char) -> bool {
+    char.is_ascii_uppercase()
+}
+
+pub fn complement_ranges(ranges: &[(char, char)]) -> Vec<(char, char)> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_unstable();
+
+    let mut complement = Vec::new();
+    let mut next = Some('\0');
+    for (min, max) in sorted {
+        let Some(start) = next else {
+            break;
+        };
+        if start < min {
+            complement.push((start, prev_char(min)));
+        }
+        if max >= start {
+            next = next_char(max);
+        }
+    }
+    if let Some(start) = next {
+        complement.push((start, char::MAX));
+    }
+    complement
+}
+
+pub fn next_char(char: char) -> Option<char> {
+    match char {
+        '\u{D7FF}' => Some('\u{E000}'),
+        char::MAX => None,
+        _ => char::from_u32(char as u32 + 1),
+    }
+}
+
+pub fn prev_char(char: char) -> char {
+    match char {
+        '\u{E000}' => '\u{D7FF}',
+        '\0' => '\0',
+        _ => char::from_u32(char as u32 - 1).unwrap_or('\0'),
+    }
+}
+
+pub fn normalize_ranges(ranges: &[(char, char)]) -> Vec<(char, char)> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_unstable();
+
+    let mut normalized: Vec<(char, char)> = Vec::new();
+    for (min, max) in sorted {
+        if let Some(last) = normalized.last_mut()
+            && next_char(last.1).is_none_or(|next| next >= min)
+        {
+            last.1 = last.1.max(max);
+            continue;
+        }
+        normalized.push((min, max));
+    }
+    normalized
+}
+
+pub fn ranges_to_parts(ranges: &[(char, char)]) -> Vec<CharClassPart> {
+    ranges
+        .iter()
+        .map(|&(min, max)| {
+            if min == max {
+                CharClassPart::Literal { char: min }
+            } else {
+                CharClassPart::Range { min, max }
+            }
+        })
+        .collect()
+}
+
+pub fn parts_to_ranges(parts: &[CharClassPart]) -> Vec<(char, char)> {
+    parts
+        .iter()
+        .map(|part| match *part {
+            CharClassPart::Literal { char } => (char, char),
+            CharClassPart::Range { min, max } => (min, max),
+        })
+        .collect()
+}
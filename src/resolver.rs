@@ -0,0 +1,413 @@
+use std::mem;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast::{ASTNode, DEFAULT_MODE, MAX_REPETITION, Rule, RuleAction, RuleKind},
+    error::{Error, ErrorKind},
+    nfa::RuleId,
+    span::Span,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VisitState {
+    Unvisited,
+    InProgress,
+    Done,
+}
+
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    pub dependencies: Vec<Vec<(RuleId, Span)>>,
+}
+
+#[derive(Debug)]
+pub struct Resolver<'a, 'src> {
+    rules: &'a [Rule<'src>],
+    rule_ids: FxHashMap<&'src str, RuleId>,
+    modes: Vec<&'src str>,
+    graph: DependencyGraph,
+    visit_states: Vec<VisitState>,
+    path: Vec<(RuleId, Span)>,
+    nullable_rules: Vec<Option<bool>>,
+    repetition_factors: Vec<Option<usize>>,
+    errors: Vec<Error>,
+}
+
+impl<'a, 'src> Resolver<'a, 'src> {
+    pub fn new(rules: &'a [Rule<'src>]) -> Self {
+        Self {
+            rules,
+            rule_ids: FxHashMap::default(),
+            modes: vec![DEFAULT_MODE],
+            graph: DependencyGraph::default(),
+            visit_states: vec![VisitState::Unvisited; rules.len()],
+            path: Vec::new(),
+            nullable_rules: vec![None; rules.len()],
+            repetition_factors: vec![None; rules.len()],
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn resolve(&mut self) -> (DependencyGraph, Vec<&'src str>, Vec<Error>) {
+        self.collect_names();
+        self.collect_modes();
+        self.check_actions();
+        self.collect_dependencies();
+        for rule_id in 0..self.rules.len() {
+            self.find_cycles(rule_id);
+        }
+        self.find_nullable_rules();
+        for rule_id in 0..self.rules.len() {
+            self.repetition_factor(rule_id);
+        }
+
+        let graph = mem::take(&mut self.graph);
+        let modes = mem::take(&mut self.modes);
+        let errors = mem::take(&mut self.errors);
+        (graph, modes, errors)
+    }
+
+    fn collect_names(&mut self) {
+        for (rule_id, rule) in self.rules.iter().enumerate() {
+            match self.rule_ids.get(rule.name) {
+                Some(&first_id) => self.errors.push(Error {
+                    kind: ErrorKind::DuplicateRule {
+                        name: rule.name.to_string(),
+                        first_span: self.rules[first_id].name_span,
+                    },
+                    span: rule.name_span,
+                }),
+                None => {
+                    self.rule_ids.insert(rule.name, rule_id);
+                }
+            }
+        }
+    }
+
+    fn collect_modes(&mut self) {
+        for rule in self.rules {
+            if !matches!(rule.kind, RuleKind::Helper) && !self.modes.contains(&rule.mode) {
+                self.modes.push(rule.mode);
+            }
+        }
+    }
+
+    fn check_actions(&mut self) {
+        for rule in self.rules {
+            let Some(action) = rule.action else {
+                continue;
+            };
+            match action {
+                RuleAction::Push { span, .. } | RuleAction::Pop { span } | RuleAction::Enter { span, .. }
+                    if matches!(rule.kind, RuleKind::Helper) =>
+                {
+                    self.errors.push(Error {
+                        kind: ErrorKind::HelperRuleAction,
+                        span,
+                    });
+                }
+                RuleAction::Push { mode, span } | RuleAction::Enter { mode, span } if !self.modes.contains(&mode) => {
+                    self.errors.push(Error {
+                        kind: ErrorKind::UndefinedMode { name: mode.to_string() },
+                        span,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_dependencies(&mut self) {
+        for rule in self.rules {
+            let mut dependencies = Vec::new();
+            self.collect_references(&rule.pattern, &mut dependencies);
+            self.graph.dependencies.push(dependencies);
+        }
+    }
+
+    fn collect_references(&mut self, node: &ASTNode<'src>, dependencies: &mut Vec<(RuleId, Span)>) {
+        match node {
+            ASTNode::Reference { name, span } => match self.rule_ids.get(name) {
+                Some(&rule_id) => dependencies.push((rule_id, *span)),
+                None => self.errors.push(Error {
+                    kind: ErrorKind::UndefinedReference {
+                        name: name.to_string(),
+                    },
+                    span: *span,
+                }),
+            },
+            ASTNode::Alternation { left, right }
+            | ASTNode::Concat { left, right }
+            | ASTNode::TrailingContext {
+                head: left,
+                tail: right,
+                ..
+            } => {
+                self.collect_references(left, dependencies);
+                self.collect_references(right, dependencies);
+            }
+            ASTNode::Repeat0 { inner, .. }
+            | ASTNode::Repeat1 { inner }
+            | ASTNode::Optional { inner, .. }
+            | ASTNode::Group { inner, .. }
+            | ASTNode::CaseInsensitive { inner, .. }
+            | ASTNode::Range { inner, .. } => self.collect_references(inner, dependencies),
+            ASTNode::Literal { .. }
+            | ASTNode::CharClass { .. }
+            | ASTNode::AnyChar
+            | ASTNode::LineStart { .. }
+            | ASTNode::LineEnd { .. }
+            | ASTNode::Error => {}
+        }
+    }
+
+    fn find_cycles(&mut self, rule_id: RuleId) {
+        match self.visit_states[rule_id] {
+            VisitState::Done => return,
+            VisitState::InProgress => unreachable!("cycles are detected before revisiting a rule"),
+            VisitState::Unvisited => {}
+        }
+
+        self.visit_states[rule_id] = VisitState::InProgress;
+        for (dependency, span) in self.graph.dependencies[rule_id].clone() {
+            match self.visit_states[dependency] {
+                VisitState::Done => {}
+                VisitState::InProgress => self.report_cycle(rule_id, dependency, span),
+                VisitState::Unvisited => {
+                    self.path.push((rule_id, span));
+                    self.find_cycles(dependency);
+                    self.path.pop();
+                }
+            }
+        }
+        self.visit_states[rule_id] = VisitState::Done;
+    }
+
+    fn find_nullable_rules(&mut self) {
+        for rule in self.rules {
+            if matches!(rule.kind, RuleKind::Helper) {
+                continue;
+            }
+            let empty_spans = self.empty_spans(&rule.pattern);
+            if !empty_spans.is_empty() {
+                self.errors.push(Error {
+                    kind: ErrorKind::NullableRule {
+                        name: rule.name.to_string(),
+                        empty_spans,
+                    },
+                    span: rule.name_span,
+                });
+            }
+        }
+    }
+
+    fn is_nullable(&mut self, rule_id: RuleId) -> bool {
+        if let Some(is_nullable) = self.nullable_rules[rule_id] {
+            return is_nullable;
+        }
+        self.nullable_rules[rule_id] = Some(false);
+        let is_nullable = !self.empty_spans(&self.rules[rule_id].pattern).is_empty();
+        self.nullable_rules[rule_id] = Some(is_nullable);
+        is_nullable
+    }
+
+    fn empty_spans(&mut self, node: &ASTNode<'src>) -> Vec<Span> {
+        match node {
+            ASTNode::Reference { name, span } => match self.rule_ids.get(name).copied() {
+                Some(rule_id) if self.is_nullable(rule_id) => vec![*span],
+                _ => Vec::new(),
+            },
+            ASTNode::Alternation { left, right } => {
+                let left = self.empty_spans(left);
+                if left.is_empty() { self.empty_spans(right) } else { left }
+            }
+            ASTNode::Concat { left, right } => {
+                let mut left = self.empty_spans(left);
+                let right = self.empty_spans(right);
+                if left.is_empty() || right.is_empty() {
+                    return Vec::new();
+                }
+                left.extend(right);
+                left
+            }
+            ASTNode::Repeat0 { span, .. }
+            | ASTNode::Optional { span, .. }
+            | ASTNode::Range { min: 0, span, .. }
+            | ASTNode::LineStart { span }
+            | ASTNode::LineEnd { span } => vec![*span],
+            ASTNode::Repeat1 { inner }
+            | ASTNode::Group { inner, .. }
+            | ASTNode::CaseInsensitive { inner, .. }
+            | ASTNode::Range { inner, .. }
+            | ASTNode::TrailingContext { head: inner, .. } => self.empty_spans(inner),
+            ASTNode::Literal { .. } | ASTNode::CharClass { .. } | ASTNode::AnyChar | ASTNode::Error => Vec::new(),
+        }
+    }
+
+    fn repetition_factor(&mut self, rule_id: RuleId) -> usize {
+        if let Some(factor) = self.repetition_factors[rule_id] {
+            return factor;
+        }
+        self.repetition_factors[rule_id] = Some(1);
+        let factor = self.expansion_factor(&self.rules[rule_id].pattern);
+        self.repetition_factors[rule_id] = Some(factor);
+        factor
+    }
+
+    fn expansion_factor(&mut self, node: &ASTNode<'src>) -> usize {
+        match node {
+            ASTNode::Reference { name, .. } => match self.rule_ids.get(name).copied() {
+                Some(rule_id) => self.repetition_factor(rule_id),
+                None => 1,
+            },
+            ASTNode::Alternation { left, right }
+            | ASTNode::Concat { left, right }
+            | ASTNode::TrailingContext {
+                head: left,
+                tail: right,
+                ..
+            } => self.expansion_factor(left).max(self.expansion_factor(right)),
+            ASTNode::Range { inner, min, max, span } => {
+                let factor = self.expansion_factor(inner).saturating_mul(max.unwrap_or(*min).max(1));
+                if factor <= MAX_REPETITION {
+                    return factor;
+                }
+                self.errors.push(Error {
+                    kind: ErrorKind::RepetitionTooLarge {
+                        value: factor,
+                        limit: MAX_REPETITION,
+                    },
+                    span: *span,
+                });
+                1
+            }
+            ASTNode::Repeat0 { inner, .. }
+            | ASTNode::Repeat1 { inner }
+            | ASTNode::Optional { inner, .. }
+            | ASTNode::Group { inner, .. }
+            | ASTNode::CaseInsensitive { inner, .. } => self.expansion_factor(inner),
+            ASTNode::Literal { .. }
+            | ASTNode::CharClass { .. }
+            | ASTNode::AnyChar
+            | ASTNode::LineStart { .. }
+            | ASTNode::LineEnd { .. }
+            | ASTNode::Error => 1,
+        }
+    }
+
+    fn report_cycle(&mut self, from: RuleId, to: RuleId, span: Span) {
+        let cycle_start = self
+            .path
+            .iter()
+            .position(|&(rule_id, _)| rule_id == to)
+            .unwrap_or(self.path.len());
+
+        let mut names: Vec<&str> = self.path[cycle_start..]
+            .iter()
+            .map(|&(rule_id, _)| self.rules[rule_id].name)
+            .collect();
+        names.push(self.rules[from].name);
+        names.push(self.rules[to].name);
+
+        self.errors.push(Error {
+            kind: ErrorKind::RecursiveReference {
+                name: self.rules[to].name.to_string(),
+                cycle: names.join(" -> "),
+            },
+            span,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use miette::Diagnostic;
+
+    use crate::{error::ErrorKind, lexer::Lexer, parser::Parser, span::Span};
+
+    use super::Resolver;
+
+    fn resolve_errors(source: &str) -> Vec<(ErrorKind, Span)> {
+        let (tokens, _) = Lexer::new(source).lex();
+        let (rules, _, _) = Parser::new(&tokens).parse();
+        let (_, _, errors) = Resolver::new(&rules).resolve();
+        errors.into_iter().map(|error| (error.kind, error.span)).collect()
+    }
+
+    fn span_of(source: &str, needle: &str) -> Span {
+        let start = source.find(needle).unwrap();
+        Span::from((start, start + needle.len()))
+    }
+
+    #[test]
+    fn duplicate_and_undefined_names_are_reported() {
+        let source = "word = [a-z]+\nword = x\nnumber = {digit}+\n";
+        let first = span_of(source, "word");
+        let second = source.rfind("word").unwrap();
+        let second = Span::from((second, second + "word".len()));
+        assert_eq!(
+            resolve_errors(source),
+            [
+                (ErrorKind::DuplicateRule { name: String::from("word"), first_span: first }, second),
+                (ErrorKind::UndefinedReference { name: String::from("digit") }, span_of(source, "{digit}")),
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles_report_the_reference_path() {
+        let source = "helper a = x{b}\nhelper b = y{c}\nhelper c = z{a}\n";
+        assert_eq!(
+            resolve_errors(source),
+            [(
+                ErrorKind::RecursiveReference {
+                    name: String::from("a"),
+                    cycle: String::from("a -> b -> c -> a"),
+                },
+                span_of(source, "{a}"),
+            )]
+        );
+    }
+
+    #[test]
+    fn nullable_rules_label_every_empty_part() {
+        let source = "helper sign = [+-]?\nnumber = {sign}[0-9]*\nskip space = \\s+\n";
+        let (tokens, _) = Lexer::new(source).lex();
+        let (rules, _, _) = Parser::new(&tokens).parse();
+        let (_, _, errors) = Resolver::new(&rules).resolve();
+        assert_eq!(errors.len(), 1);
+
+        let labels: Vec<(String, Span)> = errors[0]
+            .labels()
+            .unwrap()
+            .map(|label| (label.label().unwrap().to_string(), Span::from((label.offset(), label.offset() + label.len()))))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                (String::from("in this rule"), span_of(source, "number")),
+                (String::from("matches the empty string"), span_of(source, "{sign}")),
+                (String::from("matches the empty string"), span_of(source, "[0-9]*")),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_repetitions_share_one_budget() {
+        let source = "x = (((ab){100}){100}){100}c\n";
+        assert_eq!(
+            resolve_errors(source),
+            [(ErrorKind::RepetitionTooLarge { value: 10000, limit: 1000 }, span_of(source, "((ab){100}){100}"))]
+        );
+    }
+
+    #[test]
+    fn repetitions_multiply_through_references() {
+        let source = "helper pair = (ab){100}\nx = {pair}{20}\n";
+        assert_eq!(
+            resolve_errors(source),
+            [(ErrorKind::RepetitionTooLarge { value: 2000, limit: 1000 }, span_of(source, "{pair}{20}"))]
+        );
+    }
+}
@@ -12,6 +12,8 @@ pub enum TokenKind<'src> {
     Number { num: usize },
     Identifier { name: &'src str },
     ShorthandClass { char: char },
+    UnicodeClass { name: &'src str, negated: bool },
+    Reference { name: &'src str }, // {name}
     Pipe,        // |
     Star,        // *
     Plus,        // +
@@ -27,7 +29,15 @@ pub enum TokenKind<'src> {
     Period,      // .
     Caret,       // ^
     Dollar,      // $
+    Slash,       // /
+    Quote,       // " or '
     Equals,      // =
+    Arrow,       // ->
     Newline,     // \n
+    Helper,      // helper
+    Skip,        // skip
+    Mode,        // mode
+    NoCase,      // nocase
+    Comment,     // // or /* */
     Error,
 }
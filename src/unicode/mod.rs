@@ -0,0 +1,110 @@
+use crate::regex::{complement_ranges, normalize_ranges};
+
+use self::tables::{BINARY_PROPERTIES, CASE_FOLDING, GENERAL_CATEGORIES, SCRIPTS};
+
+mod tables;
+
+type Property = (&'static [&'static str], &'static [(char, char)]);
+
+const CATEGORY_GROUPS: &[(&[&str], &[&str])] = &[
+    (&["C", "Other"], &["Cc", "Cf", "Cn", "Co"]),
+    (&["L", "Letter"], &["Ll", "Lm", "Lo", "Lt", "Lu"]),
+    (&["LC", "Cased_Letter"], &["Ll", "Lt", "Lu"]),
+    (&["M", "Mark", "Combining_Mark"], &["Mc", "Me", "Mn"]),
+    (&["N", "Number"], &["Nd", "Nl", "No"]),
+    (&["P", "Punctuation", "Punct"], &["Pc", "Pd", "Pe", "Pf", "Pi", "Po", "Ps"]),
+    (&["S", "Symbol"], &["Sc", "Sk", "Sm", "So"]),
+    (&["Z", "Separator"], &["Zl", "Zp", "Zs"]),
+];
+
+const UNASSIGNED: &[&str] = &["Cn", "Unassigned"];
+const SURROGATE: &[&str] = &["Cs", "Surrogate"];
+
+pub fn property_ranges(query: &str) -> Option<Vec<(char, char)>> {
+    let Some((key, value)) = query.split_once(['=', ':']) else {
+        return general_category(query)
+            .or_else(|| script(query))
+            .or_else(|| binary_property(query))
+            .or_else(|| special_property(query));
+    };
+
+    match loose_name(key).as_str() {
+        "gc" | "generalcategory" => general_category(value),
+        "sc" | "script" => script(value),
+        _ => None,
+    }
+}
+
+pub fn case_fold_ranges(ranges: &[(char, char)]) -> Vec<(char, char)> {
+    let mut folded = ranges.to_vec();
+    for &(min, max) in ranges {
+        let start = CASE_FOLDING.partition_point(|&(char, _)| char < min);
+        for &(_, others) in CASE_FOLDING[start..].iter().take_while(|&&(char, _)| char <= max) {
+            folded.extend(others.iter().map(|&other| (other, other)));
+        }
+    }
+    normalize_ranges(&folded)
+}
+
+fn general_category(name: &str) -> Option<Vec<(char, char)>> {
+    if let Some(ranges) = find_table(GENERAL_CATEGORIES, name) {
+        return Some(ranges.to_vec());
+    }
+    if is_alias(UNASSIGNED, name) {
+        return Some(unassigned());
+    }
+    if is_alias(SURROGATE, name) {
+        return Some(Vec::new());
+    }
+
+    let (_, categories) = CATEGORY_GROUPS.iter().find(|(aliases, _)| is_alias(aliases, name))?;
+    let mut ranges = Vec::new();
+    for category in *categories {
+        ranges.extend(general_category(category).unwrap_or_default());
+    }
+    Some(normalize_ranges(&ranges))
+}
+
+fn script(name: &str) -> Option<Vec<(char, char)>> {
+    find_table(SCRIPTS, name).map(<[(char, char)]>::to_vec)
+}
+
+fn binary_property(name: &str) -> Option<Vec<(char, char)>> {
+    find_table(BINARY_PROPERTIES, name).map(<[(char, char)]>::to_vec)
+}
+
+fn special_property(name: &str) -> Option<Vec<(char, char)>> {
+    match loose_name(name).as_str() {
+        "any" => Some(vec![('\0', char::MAX)]),
+        "ascii" => Some(vec![('\0', '\x7F')]),
+        "assigned" => Some(complement_ranges(&unassigned())),
+        _ => None,
+    }
+}
+
+fn unassigned() -> Vec<(char, char)> {
+    let assigned: Vec<(char, char)> = GENERAL_CATEGORIES
+        .iter()
+        .flat_map(|(_, ranges)| ranges.iter().copied())
+        .collect();
+    complement_ranges(&assigned)
+}
+
+fn find_table(tables: &[Property], name: &str) -> Option<&'static [(char, char)]> {
+    tables
+        .iter()
+        .find(|(aliases, _)| is_alias(aliases, name))
+        .map(|&(_, ranges)| ranges)
+}
+
+fn is_alias(aliases: &[&str], name: &str) -> bool {
+    let name = loose_name(name);
+    aliases.iter().any(|alias| loose_name(alias) == name)
+}
+
+fn loose_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
@@ -0,0 +1,83 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteRange {
+    pub start: u8,
+    pub end: u8,
+}
+
+const SURROGATE_START: u32 = 0xD800;
+const SURROGATE_END: u32 = 0xDFFF;
+const ENCODING_BOUNDARIES: [u32; 3] = [0x7F, 0x7FF, 0xFFFF];
+
+pub fn utf8_sequences(min: char, max: char) -> Vec<Vec<ByteRange>> {
+    let mut sequences = Vec::new();
+    let mut stack = vec![(min as u32, max as u32)];
+
+    'ranges: while let Some((start, mut end)) = stack.pop() {
+        if start > end {
+            continue;
+        }
+        if start < SURROGATE_START && end > SURROGATE_END {
+            stack.push((SURROGATE_END + 1, end));
+            end = SURROGATE_START - 1;
+        }
+
+        loop {
+            for boundary in ENCODING_BOUNDARIES {
+                if start <= boundary && boundary < end {
+                    stack.push((boundary + 1, end));
+                    end = boundary;
+                }
+            }
+
+            if end <= 0x7F {
+                sequences.push(vec![ByteRange {
+                    start: start as u8,
+                    end: end as u8,
+                }]);
+                continue 'ranges;
+            }
+
+            let mut is_split = false;
+            for i in 1..4 {
+                let mask: u32 = (1 << (6 * i)) - 1;
+                if start & !mask == end & !mask {
+                    continue;
+                }
+                if start & mask != 0 {
+                    stack.push(((start | mask) + 1, end));
+                    end = start | mask;
+                    is_split = true;
+                    break;
+                }
+                if end & mask != mask {
+                    stack.push((end & !mask, end));
+                    end = (end & !mask) - 1;
+                    is_split = true;
+                    break;
+                }
+            }
+            if is_split {
+                continue;
+            }
+
+            let start_bytes = encode(start);
+            let end_bytes = encode(end);
+            let sequence = start_bytes
+                .iter()
+                .zip(end_bytes.iter())
+                .map(|(&start, &end)| ByteRange { start, end })
+                .collect();
+            sequences.push(sequence);
+            continue 'ranges;
+        }
+    }
+
+    sequences.sort_unstable();
+    sequences
+}
+
+fn encode(value: u32) -> Vec<u8> {
+    let char = char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER);
+    let mut buffer = [0; 4];
+    char.encode_utf8(&mut buffer).as_bytes().to_vec()
+}
@@ -12,7 +12,7 @@ pub struct Warning {
 #[derive(Error, Diagnostic, Debug)]
 pub enum WarningKind {
     #[error("Identical Rule Patterns")]
-    #[diagnostic(help("Consider removing the paranthesis"), severity(warning))]
+    #[diagnostic(help("The rules '{name1}' and '{name2}' match the same input, consider removing one of them"), severity(warning))]
     IdenticalRulePatterns {
         name1: String,
         span1: SourceSpan,
@@ -29,8 +29,32 @@ pub enum WarningKind {
     UnusedHelperRule,
 
     #[error("Unnecessary Range Boundary")]
-    #[diagnostic(help("Conside replacing {{{value},{value}}} with {{{value}}}"), severity(warning))]
+    #[diagnostic(help("Consider replacing {{{value},{value}}} with {{{value}}}"), severity(warning))]
     UnnecessaryRangeBoundary { value: usize },
+
+    #[error("Ambiguous Rules")]
+    #[diagnostic(
+        help("Both rules match \"{example}\", '{winner}' takes precedence because it is declared first"),
+        severity(warning)
+    )]
+    AmbiguousRules {
+        winner: String,
+        winner_span: SourceSpan,
+        loser: String,
+        loser_span: SourceSpan,
+        example: String,
+    },
+
+    #[error("Unreachable Rule")]
+    #[diagnostic(
+        help("The rule '{name}' is unreachable, shadowed by {shadowed_by}, consider declaring it first"),
+        severity(warning)
+    )]
+    UnreachableRule {
+        name: String,
+        shadowed_by: String,
+        shadowing_spans: Vec<SourceSpan>,
+    },
 }
 
 impl Diagnostic for Warning {
@@ -62,6 +86,32 @@ impl Diagnostic for Warning {
                 Some(Box::new(std::iter::once(label1).chain(std::iter::once(label2))))
             }
 
+            WarningKind::AmbiguousRules {
+                winner_span,
+                loser_span,
+                ..
+            } => {
+                let text1 = String::from("takes precedence");
+                let label1 = LabeledSpan::new_with_span(Some(text1), winner_span);
+
+                let text2 = String::from("overlaps with this rule");
+                let label2 = LabeledSpan::new_with_span(Some(text2), loser_span);
+                Some(Box::new(std::iter::once(label1).chain(std::iter::once(label2))))
+            }
+
+            WarningKind::UnreachableRule {
+                ref shadowing_spans,
+                ..
+            } => {
+                let shadowing_labels = shadowing_spans
+                    .iter()
+                    .map(|&span| LabeledSpan::new_with_span(Some(String::from("shadows it")), span));
+
+                let text = String::from("never matches");
+                let label = LabeledSpan::new_with_span(Some(text), self.span);
+                Some(Box::new(shadowing_labels.chain(std::iter::once(label))))
+            }
+
             _ => {
                 let text = String::from("here");
                 let label = LabeledSpan::new_with_span(Some(text), self.span);